@@ -0,0 +1,208 @@
+/**
+ * BitBrowser Cache
+ * BitBrowser 安装路径的查找缓存
+ *
+ * 缓存保存在应用配置目录的 bitbrowser_discovery.json 中，记录：
+ * - 路径和找到该路径的查找策略
+ * - 可执行文件的大小和修改时间（文件变化后缓存失效）
+ * - 检测到的版本和缓存时间
+ *
+ * 旧版本的 ~/.bitbrowser/bitbrowser_path_cache.txt 会在首次读取时迁移。
+ */
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config_manager::get_config_dir;
+use crate::config_store;
+
+/// 缓存文件名
+const CACHE_FILE_NAME: &str = "bitbrowser_discovery.json";
+
+/// 旧版本的缓存文件（~/.bitbrowser/ 下的纯文本路径）
+const LEGACY_CACHE_FILE_NAME: &str = "bitbrowser_path_cache.txt";
+
+/// 从旧缓存迁移的记录使用的策略标识
+const LEGACY_STRATEGY: &str = "legacy_cache";
+
+/// 路径查找缓存
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveryCache {
+    pub path: String,
+    /// 找到该路径的查找策略
+    pub strategy: String,
+    /// 可执行文件大小（字节）
+    pub exe_size: u64,
+    /// 可执行文件修改时间（Unix 时间戳，秒）
+    pub exe_modified: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// 缓存时间（Unix 时间戳，毫秒）
+    pub cached_at: u64,
+}
+
+impl DiscoveryCache {
+    /// 根据可执行文件的当前状态创建缓存记录
+    pub fn new(path: &str, strategy: &str, version: Option<String>) -> Result<Self, String> {
+        let (exe_size, exe_modified) = file_fingerprint(Path::new(path))?;
+        Ok(DiscoveryCache {
+            path: path.to_string(),
+            strategy: strategy.to_string(),
+            exe_size,
+            exe_modified,
+            version,
+            cached_at: get_timestamp(),
+        })
+    }
+
+    /// 校验缓存的可执行文件是否未变化，返回失效原因
+    fn validate(&self, fingerprint: Result<(u64, u64), String>) -> Result<(), String> {
+        let (size, modified) = fingerprint?;
+        if size != self.exe_size || modified != self.exe_modified {
+            return Err("可执行文件已变化".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 读取可执行文件的大小和修改时间
+fn file_fingerprint(path: &Path) -> Result<(u64, u64), String> {
+    let metadata = fs::metadata(path).map_err(|e| format!("无法读取文件信息: {}", e))?;
+    if !metadata.is_file() {
+        return Err("路径不是文件".to_string());
+    }
+
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs());
+    Ok((metadata.len(), modified))
+}
+
+fn cache_file_path() -> PathBuf {
+    get_config_dir().join(CACHE_FILE_NAME)
+}
+
+/// 读取缓存（不校验）
+pub fn read() -> Option<DiscoveryCache> {
+    let content = fs::read_to_string(cache_file_path()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 读取并校验缓存，可执行文件变化或不存在时清除缓存
+pub fn load() -> Option<DiscoveryCache> {
+    let cache = match read() {
+        Some(cache) => cache,
+        None => migrate_legacy_cache()?,
+    };
+
+    match cache.validate(file_fingerprint(Path::new(&cache.path))) {
+        Ok(()) => {
+            println!("✓ 从缓存读取路径: {}（{}）", cache.path, cache.strategy);
+            Some(cache)
+        }
+        Err(reason) => {
+            println!("⚠ 缓存路径已失效（{}），将重新搜索", reason);
+            clear();
+            None
+        }
+    }
+}
+
+/// 写入缓存
+pub fn store(path: &str, strategy: &str, version: Option<String>) {
+    let cache = match DiscoveryCache::new(path, strategy, version) {
+        Ok(cache) => cache,
+        Err(e) => {
+            println!("⚠ 无法缓存路径 {}: {}", path, e);
+            return;
+        }
+    };
+
+    match serde_json::to_string_pretty(&cache) {
+        // 原子写入，崩溃时不会留下半截的缓存文件
+        Ok(json) => match config_store::write_atomic(&cache_file_path(), json.as_bytes()) {
+            Ok(_) => println!("✓ 路径已缓存: {}（{}）", path, strategy),
+            Err(e) => println!("⚠ 写入路径缓存失败: {}", e),
+        },
+        Err(e) => println!("⚠ 序列化路径缓存失败: {}", e),
+    }
+}
+
+/// 清除缓存
+pub fn clear() {
+    let cache_path = cache_file_path();
+    if cache_path.exists() {
+        let _ = fs::remove_file(&cache_path);
+        println!("✓ 缓存已清除");
+    }
+}
+
+/// 将旧版本的纯文本缓存迁移为结构化缓存
+fn migrate_legacy_cache() -> Option<DiscoveryCache> {
+    let legacy_path = dirs::home_dir()?.join(".bitbrowser").join(LEGACY_CACHE_FILE_NAME);
+    let content = fs::read_to_string(&legacy_path).ok()?;
+    let _ = fs::remove_file(&legacy_path);
+
+    let path = content.trim();
+    let cache = DiscoveryCache::new(path, LEGACY_STRATEGY, None).ok()?;
+    store(path, LEGACY_STRATEGY, None);
+    println!("✓ 已迁移旧版路径缓存: {}", path);
+    Some(cache)
+}
+
+/// 获取当前时间戳（毫秒）
+fn get_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_invalidated_when_binary_changes() {
+        let dir = std::env::temp_dir().join(format!("bb-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let exe = dir.join("BitBrowser.exe");
+        fs::write(&exe, b"v1").unwrap();
+
+        let exe_str = exe.to_string_lossy().to_string();
+        let cache = DiscoveryCache::new(&exe_str, "registry", Some("1.0".to_string())).unwrap();
+        assert_eq!(cache.exe_size, 2);
+        assert!(cache.validate(file_fingerprint(&exe)).is_ok());
+
+        // 文件大小变化
+        fs::write(&exe, b"v2-longer").unwrap();
+        assert!(cache.validate(file_fingerprint(&exe)).is_err());
+
+        // 文件被删除
+        fs::remove_file(&exe).unwrap();
+        assert!(cache.validate(file_fingerprint(&exe)).is_err());
+        assert!(DiscoveryCache::new(&exe_str, "registry", None).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let cache = DiscoveryCache {
+            path: "C:\\BitBrowser\\BitBrowser.exe".to_string(),
+            strategy: "start_menu".to_string(),
+            exe_size: 1024,
+            exe_modified: 1_700_000_000,
+            version: None,
+            cached_at: 1,
+        };
+        let json = serde_json::to_value(&cache).unwrap();
+        assert_eq!(json["exeSize"], 1024);
+        assert!(json.get("version").is_none());
+        assert_eq!(serde_json::from_value::<DiscoveryCache>(json).unwrap(), cache);
+    }
+}
@@ -0,0 +1,601 @@
+/**
+ * BitBrowser Client
+ * BitBrowser 本地 API 的强类型客户端
+ *
+ * 功能：
+ * - 复用同一个 HTTP 客户端（禁用代理）
+ * - 为 /browser 和 /group 系列接口提供强类型的请求/响应结构
+ * - 统一的错误类型，响应格式异常时返回错误而不是默认值
+ */
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::bitbrowser_manager::get_api_base_url;
+
+/// API Token 请求头名称（新版 BitBrowser 开启本地 API 鉴权时需要）
+const API_TOKEN_HEADER: &str = "x-api-key";
+
+/// 共享的 HTTP 客户端（Token 变更时重建）
+static HTTP_CLIENT: RwLock<Option<reqwest::Client>> = RwLock::new(None);
+
+/// 当前使用的 API Token
+static API_TOKEN: RwLock<Option<String>> = RwLock::new(None);
+
+/// 构建访问 BitBrowser API 的 HTTP 客户端
+///
+/// 重要：reqwest 默认会使用系统代理，即使访问 localhost 也可能通过代理，
+/// 导致 502 错误。使用 .no_proxy() 禁用代理可以解决此问题。
+fn build_http_client(token: Option<&str>) -> reqwest::Client {
+    let mut headers = reqwest::header::HeaderMap::new();
+    if let Some(token) = token {
+        match reqwest::header::HeaderValue::from_str(token) {
+            Ok(mut value) => {
+                value.set_sensitive(true);
+                headers.insert(API_TOKEN_HEADER, value);
+            }
+            Err(_) => eprintln!("⚠ BitBrowser API Token 包含非法字符，已忽略"),
+        }
+    }
+
+    reqwest::Client::builder()
+        .no_proxy() // 禁用代理（关键！）
+        .default_headers(headers)
+        .build()
+        .expect("Failed to create HTTP client")
+}
+
+/// 获取共享的 HTTP 客户端（已附带 API Token）
+pub fn shared_http_client() -> reqwest::Client {
+    if let Some(client) = HTTP_CLIENT.read().unwrap().as_ref() {
+        return client.clone();
+    }
+
+    let mut slot = HTTP_CLIENT.write().unwrap();
+    slot.get_or_insert_with(|| build_http_client(API_TOKEN.read().unwrap().as_deref()))
+        .clone()
+}
+
+/// 设置 BitBrowser API Token（None 或空字符串表示不鉴权）
+pub fn set_api_token(token: Option<String>) {
+    let token = token
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+
+    *API_TOKEN.write().unwrap() = token;
+    // 下次获取时按新 Token 重建客户端
+    *HTTP_CLIENT.write().unwrap() = None;
+}
+
+// ==================== 错误类型 ====================
+
+/// BitBrowser API 错误
+#[derive(Debug, Clone)]
+pub enum BitBrowserError {
+    /// 请求未能发出或连接失败（超时、拒绝连接等）
+    RequestFailed(String),
+    /// HTTP 状态码异常
+    HttpError(u16),
+    /// 响应体无法解析为预期结构
+    InvalidResponse(String),
+    /// BitBrowser 返回 success=false
+    ApiFailed(String),
+    /// 写入后回读校验失败（配置被并发修改覆盖）
+    Conflict(String),
+}
+
+impl BitBrowserError {
+    /// 是否为 BitBrowser 明确拒绝的请求（HTTP 状态异常、success=false 或写入冲突）
+    pub fn is_rejected(&self) -> bool {
+        matches!(
+            self,
+            BitBrowserError::HttpError(_)
+                | BitBrowserError::ApiFailed(_)
+                | BitBrowserError::Conflict(_)
+        )
+    }
+}
+
+impl std::fmt::Display for BitBrowserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BitBrowserError::RequestFailed(msg) => write!(f, "请求失败: {}", msg),
+            BitBrowserError::HttpError(code) => write!(f, "HTTP 错误: {}", code),
+            BitBrowserError::InvalidResponse(msg) => write!(f, "无效响应: {}", msg),
+            BitBrowserError::ApiFailed(msg) => write!(f, "{}", msg),
+            BitBrowserError::Conflict(msg) => write!(f, "配置冲突: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for BitBrowserError {}
+
+impl From<BitBrowserError> for String {
+    fn from(error: BitBrowserError) -> Self {
+        error.to_string()
+    }
+}
+
+// ==================== 请求/响应结构 ====================
+
+/// BitBrowser API 统一响应结构
+#[derive(Debug, Deserialize)]
+struct ApiEnvelope<T> {
+    success: bool,
+    #[serde(default)]
+    msg: Option<String>,
+    data: Option<T>,
+}
+
+/// 按 ID 操作浏览器的请求体（/browser/detail、/browser/close、/browser/delete）
+#[derive(Debug, Clone, Serialize)]
+struct BrowserIdRequest<'a> {
+    id: &'a str,
+}
+
+/// 分页请求体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageRequest {
+    pub page: i64,
+    pub page_size: i64,
+}
+
+impl PageRequest {
+    pub fn new(page: i64, page_size: i64) -> Self {
+        PageRequest { page, page_size }
+    }
+}
+
+/// BitBrowser 单页最大条数（pageSize 不能超过 100）
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// 浏览器列表请求体（/browser/list），筛选条件由服务端处理
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowserListRequest {
+    pub page: i64,
+    pub page_size: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remark: Option<String>,
+}
+
+impl BrowserListRequest {
+    pub fn new(page: i64, page_size: i64) -> Self {
+        BrowserListRequest {
+            page,
+            page_size,
+            ..Default::default()
+        }
+    }
+}
+
+/// 浏览器列表响应数据（/browser/list）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowserListData {
+    pub list: Vec<BrowserProfile>,
+    #[serde(default)]
+    pub total_num: i64,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// 浏览器配置（/browser/list 列表项、/browser/detail、/browser/update）
+///
+/// 只声明本应用用到的字段，其余字段保存在 `extra` 中，
+/// 保证读取后原样写回时不会丢失 BitBrowser 的其他配置。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowserProfile {
+    /// 浏览器 ID（创建时为空）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remark: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_name: Option<String>,
+    /// Cookie：/browser/detail 返回 JSON 字符串，/browser/update 接收数组
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cookie: Option<serde_json::Value>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl BrowserProfile {
+    /// 解析 Cookie 字段（兼容字符串和数组两种格式）
+    pub fn cookies(&self) -> Result<Vec<BrowserCookie>, BitBrowserError> {
+        match &self.cookie {
+            None => Ok(Vec::new()),
+            Some(value) => BrowserCookie::parse_field(value),
+        }
+    }
+
+    /// 以数组格式设置 Cookie（/browser/update 接收的格式）
+    pub fn set_cookies(&mut self, cookies: &[BrowserCookie]) {
+        self.cookie = Some(serde_json::to_value(cookies).unwrap_or_default());
+    }
+}
+
+/// 浏览器 Cookie
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserCookie {
+    pub name: String,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl BrowserCookie {
+    /// 解析 cookie 字段的值（兼容 JSON 字符串和数组两种格式）
+    pub fn parse_field(value: &serde_json::Value) -> Result<Vec<BrowserCookie>, BitBrowserError> {
+        match value {
+            serde_json::Value::Null => Ok(Vec::new()),
+            serde_json::Value::String(s) if s.trim().is_empty() => Ok(Vec::new()),
+            serde_json::Value::String(s) => serde_json::from_str(s)
+                .map_err(|e| BitBrowserError::InvalidResponse(format!("Cookie 格式错误: {}", e))),
+            value => serde_json::from_value(value.clone())
+                .map_err(|e| BitBrowserError::InvalidResponse(format!("Cookie 格式错误: {}", e))),
+        }
+    }
+
+    /// 将 "name=value; name2=value2" 格式的 Cookie 字符串解析为 Cookie 数组
+    pub fn parse_cookie_string(cookie: &str, domain: &str) -> Vec<BrowserCookie> {
+        cookie
+            .split("; ")
+            .filter_map(|pair| {
+                let (name, value) = pair.split_once('=')?;
+                Some(BrowserCookie {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                    domain: Some(domain.to_string()),
+                    extra: serde_json::Map::new(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// 打开浏览器请求体（/browser/open）
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenBrowserRequest {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load_extensions: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clear_cache_files_before_launch: Option<bool>,
+}
+
+/// 打开浏览器响应数据（/browser/open）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenBrowserData {
+    /// DevTools WebSocket 地址
+    pub ws: String,
+    /// DevTools HTTP 地址
+    pub http: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub core_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// 分组列表响应数据（/group/list）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupListData {
+    pub list: Vec<BrowserGroup>,
+    #[serde(default)]
+    pub total_num: i64,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// 浏览器分组
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowserGroup {
+    pub id: String,
+    pub group_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_num: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remark: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+// ==================== 客户端 ====================
+
+/// BitBrowser 本地 API 客户端
+#[derive(Debug, Clone)]
+pub struct BitBrowserClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl BitBrowserClient {
+    /// 使用指定的 API 基础 URL 创建客户端
+    pub fn new(base_url: impl Into<String>) -> Self {
+        BitBrowserClient {
+            http: shared_http_client(),
+            base_url: base_url.into(),
+        }
+    }
+
+    /// 使用当前配置的 API 地址创建客户端
+    pub async fn connect() -> Result<Self, BitBrowserError> {
+        let base_url = get_api_base_url()
+            .await
+            .map_err(BitBrowserError::RequestFailed)?;
+        Ok(Self::new(base_url))
+    }
+
+    /// 发送请求并解析统一响应结构，返回 data 字段
+    async fn post<B, T>(&self, path: &str, body: &B) -> Result<Option<T>, BitBrowserError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let response = self
+            .http
+            .post(format!("{}{}", self.base_url, path))
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| BitBrowserError::RequestFailed(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(BitBrowserError::HttpError(status.as_u16()));
+        }
+
+        let text = response
+            .text()
+            .await
+            .map_err(|e| BitBrowserError::InvalidResponse(format!("响应读取失败: {}", e)))?;
+
+        let envelope: ApiEnvelope<T> = serde_json::from_str(&text)
+            .map_err(|e| BitBrowserError::InvalidResponse(format!("{} ({})", e, path)))?;
+
+        if !envelope.success {
+            return Err(BitBrowserError::ApiFailed(
+                envelope
+                    .msg
+                    .filter(|m| !m.is_empty())
+                    .unwrap_or_else(|| format!("BitBrowser 请求失败 ({})", path)),
+            ));
+        }
+
+        Ok(envelope.data)
+    }
+
+    /// 发送请求并要求响应包含 data 字段
+    async fn post_data<B, T>(&self, path: &str, body: &B) -> Result<T, BitBrowserError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.post(path, body)
+            .await?
+            .ok_or_else(|| BitBrowserError::InvalidResponse(format!("缺少 data 字段 ({})", path)))
+    }
+
+    // ========== /browser/* ==========
+
+    /// 获取浏览器列表（单页）
+    pub async fn list_browsers(&self, request: &BrowserListRequest) -> Result<BrowserListData, BitBrowserError> {
+        self.post_data("/browser/list", request).await
+    }
+
+    /// 逐页遍历浏览器列表（保留 filter 中的筛选条件，从第 0 页开始）
+    pub fn browser_pages(&self, filter: BrowserListRequest) -> BrowserPager<'_> {
+        BrowserPager {
+            client: self,
+            request: BrowserListRequest {
+                page: 0,
+                page_size: MAX_PAGE_SIZE,
+                ..filter
+            },
+            fetched: 0,
+            total_num: None,
+            done: false,
+        }
+    }
+
+    /// 获取浏览器详情
+    pub async fn browser_detail(&self, id: &str) -> Result<BrowserProfile, BitBrowserError> {
+        self.post_data("/browser/detail", &BrowserIdRequest { id }).await
+    }
+
+    /// 创建或更新浏览器（profile.id 为空时创建）
+    pub async fn update_browser(&self, profile: &BrowserProfile) -> Result<BrowserProfile, BitBrowserError> {
+        self.post_data("/browser/update", profile).await
+    }
+
+    /// 打开浏览器窗口
+    pub async fn open_browser(&self, request: &OpenBrowserRequest) -> Result<OpenBrowserData, BitBrowserError> {
+        self.post_data("/browser/open", request).await
+    }
+
+    /// 关闭浏览器窗口
+    pub async fn close_browser(&self, id: &str) -> Result<(), BitBrowserError> {
+        self.post::<_, serde_json::Value>("/browser/close", &BrowserIdRequest { id })
+            .await
+            .map(|_| ())
+    }
+
+    /// 获取所有已打开的浏览器窗口（浏览器 ID -> 进程 PID）
+    pub async fn open_browser_pids(&self) -> Result<HashMap<String, u32>, BitBrowserError> {
+        Ok(self
+            .post("/browser/pids/all", &serde_json::json!({}))
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// 删除浏览器
+    pub async fn delete_browser(&self, id: &str) -> Result<(), BitBrowserError> {
+        self.post::<_, serde_json::Value>("/browser/delete", &BrowserIdRequest { id })
+            .await
+            .map(|_| ())
+    }
+
+    // ========== /group/* ==========
+
+    /// 获取分组列表
+    pub async fn list_groups(&self, page: &PageRequest) -> Result<GroupListData, BitBrowserError> {
+        self.post_data("/group/list", page).await
+    }
+}
+
+// ==================== 分页遍历 ====================
+
+/// 浏览器列表分页遍历器，逐页请求直到取满 totalNum
+pub struct BrowserPager<'a> {
+    client: &'a BitBrowserClient,
+    request: BrowserListRequest,
+    fetched: i64,
+    total_num: Option<i64>,
+    done: bool,
+}
+
+impl BrowserPager<'_> {
+    /// 获取下一页，遍历结束时返回 None
+    pub async fn next_page(&mut self) -> Option<Result<Vec<BrowserProfile>, BitBrowserError>> {
+        if self.done {
+            return None;
+        }
+
+        let data = match self.client.list_browsers(&self.request).await {
+            Ok(data) => data,
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e));
+            }
+        };
+
+        let count = data.list.len() as i64;
+        self.fetched += count;
+        self.total_num = Some(data.total_num);
+        self.request.page += 1;
+        self.done = is_last_page(count, self.request.page_size, self.fetched, data.total_num);
+
+        if count == 0 {
+            None
+        } else {
+            Some(Ok(data.list))
+        }
+    }
+
+    /// 取出全部页，合并为一个列表
+    pub async fn collect_all(mut self) -> Result<BrowserListData, BitBrowserError> {
+        let mut list = Vec::new();
+        while let Some(page) = self.next_page().await {
+            list.extend(page?);
+        }
+        Ok(BrowserListData {
+            total_num: self.total_num.unwrap_or(list.len() as i64),
+            list,
+            extra: serde_json::Map::new(),
+        })
+    }
+}
+
+/// 判断是否已到最后一页
+///
+/// 取满 totalNum、返回空页或不足一页时结束；totalNum 缺失（为 0）时只依据页大小判断
+fn is_last_page(count: i64, page_size: i64, fetched: i64, total_num: i64) -> bool {
+    count == 0 || count < page_size || (total_num > 0 && fetched >= total_num)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_list_envelope() {
+        let json = r##"{
+            "success": true,
+            "data": {
+                "page": 0,
+                "pageSize": 10,
+                "totalNum": 1,
+                "list": [{ "id": "abc", "seq": 7, "name": "#7", "groupId": "g1", "proxyType": "noproxy" }]
+            }
+        }"##;
+        let envelope: ApiEnvelope<BrowserListData> = serde_json::from_str(json).unwrap();
+        let data = envelope.data.unwrap();
+        assert_eq!(data.total_num, 1);
+        assert_eq!(data.list[0].seq, Some(7));
+        assert_eq!(data.list[0].group_id.as_deref(), Some("g1"));
+
+        // 未声明的字段原样写回
+        let value = serde_json::to_value(&data.list[0]).unwrap();
+        assert_eq!(value["proxyType"], "noproxy");
+    }
+
+    #[test]
+    fn test_list_request_filters() {
+        let request = BrowserListRequest {
+            group_id: Some("g1".to_string()),
+            ..BrowserListRequest::new(2, MAX_PAGE_SIZE)
+        };
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value["pageSize"], 100);
+        assert_eq!(value["groupId"], "g1");
+        assert!(value.get("name").is_none());
+    }
+
+    #[test]
+    fn test_is_last_page() {
+        // 1050 条，每页 100
+        assert!(!is_last_page(100, 100, 100, 1050));
+        assert!(!is_last_page(100, 100, 1000, 1050));
+        assert!(is_last_page(50, 100, 1050, 1050));
+        // 恰好整页结束
+        assert!(is_last_page(100, 100, 200, 200));
+        // 缺少 totalNum 时依据页大小
+        assert!(!is_last_page(100, 100, 100, 0));
+        assert!(is_last_page(0, 100, 100, 0));
+    }
+
+    #[test]
+    fn test_malformed_list_is_error() {
+        let json = r#"{ "success": true, "data": { "rows": [] } }"#;
+        assert!(serde_json::from_str::<ApiEnvelope<BrowserListData>>(json).is_err());
+    }
+
+    #[test]
+    fn test_cookie_string_and_array() {
+        let mut profile = BrowserProfile {
+            cookie: Some(serde_json::json!(r#"[{"name":"wxuin","value":"1","domain":".weixin.qq.com"}]"#)),
+            ..Default::default()
+        };
+        assert_eq!(profile.cookies().unwrap()[0].name, "wxuin");
+
+        profile.set_cookies(&BrowserCookie::parse_cookie_string("a=1; b=2=3", ".weixin.qq.com"));
+        let cookies = profile.cookies().unwrap();
+        assert_eq!(cookies.len(), 2);
+        assert_eq!(cookies[1].value, "2=3");
+    }
+}
@@ -9,17 +9,24 @@
 use serde::{Deserialize, Serialize};
 use sysinfo::System;
 
+use crate::bitbrowser_client::{BitBrowserClient, BitBrowserError, BrowserListRequest};
 use crate::bitbrowser_manager::{
+    api_port_from_url,
+    get_api_base_url,
+    get_running_bitbrowser_info,
+    invalidate_resolved_api_url,
     is_bitbrowser_process,
     is_bitbrowser_running,
-    find_process_using_port
+    is_local_api_url,
+    DEFAULT_API_PORT,
 };
+use crate::bitbrowser_version::{detect_client_version, ClientVersion};
+use crate::port_owner::find_process_using_port;
+use crate::bitbrowser_cache;
+use std::sync::RwLock;
 
-/// BitBrowser API 默认端口
-const API_PORT: u16 = 54345;
-
-/// BitBrowser API 基础 URL
-const API_BASE_URL: &str = "http://127.0.0.1:54345";
+/// 已连接的 BitBrowser 客户端版本缓存（断连后清除，重新连接时重新检测）
+static CLIENT_VERSION: RwLock<Option<Option<ClientVersion>>> = RwLock::new(None);
 
 /// 连接状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +34,9 @@ const API_BASE_URL: &str = "http://127.0.0.1:54345";
 pub enum ConnectionStatus {
     Connected {
         message: String,
+        /// BitBrowser 客户端版本
+        #[serde(skip_serializing_if = "Option::is_none")]
+        version: Option<ClientVersion>,
     },
     Disconnected {
         reason: DisconnectReason,
@@ -35,7 +45,7 @@ pub enum ConnectionStatus {
 }
 
 /// 断连原因
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data", rename_all = "snake_case")]
 pub enum DisconnectReason {
     /// BitBrowser 进程未运行
@@ -50,30 +60,84 @@ pub enum DisconnectReason {
     /// BitBrowser 正在初始化（进程已启动但 API 尚未就绪）
     Initializing,
 
+    /// API 鉴权失败（未配置或配置了错误的 API Token）
+    Unauthorized,
+
     /// API 错误（连接成功但响应异常）
     ApiError {
         error: String,
     },
 }
 
+impl DisconnectReason {
+    /// 断连原因类型（与序列化的 type 字段一致）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DisconnectReason::NotRunning => "not_running",
+            DisconnectReason::PortOccupied { .. } => "port_occupied",
+            DisconnectReason::Initializing => "initializing",
+            DisconnectReason::Unauthorized => "unauthorized",
+            DisconnectReason::ApiError { .. } => "api_error",
+        }
+    }
+}
+
 /// 检测 BitBrowser 连接状态
 pub async fn check_status() -> ConnectionStatus {
+    // 解析 API 地址（配置的地址或探测到的端口）
+    let base_url = match get_api_base_url().await {
+        Ok(url) => url,
+        Err(e) => {
+            return ConnectionStatus::Disconnected {
+                reason: DisconnectReason::ApiError { error: e },
+                message: "无法确定 BitBrowser API 地址".to_string(),
+            };
+        }
+    };
+
     // 尝试连接 API
-    match test_api_connection().await {
+    match test_api_connection(&base_url).await {
         Ok(_) => {
+            let version = current_client_version();
             ConnectionStatus::Connected {
-                message: "BitBrowser 已连接".to_string(),
+                message: match &version {
+                    Some(v) => format!("BitBrowser 已连接（v{}）", v.version),
+                    None => "BitBrowser 已连接".to_string(),
+                },
+                version,
             }
         }
         Err(e) => {
-            // 连接失败，诊断原因
-            diagnose_disconnect_reason(e).await
+            *CLIENT_VERSION.write().unwrap() = None;
+            // 连接失败，下次重新探测端口，并诊断原因
+            invalidate_resolved_api_url();
+            diagnose_disconnect_reason(&base_url, e).await
         }
     }
 }
 
+/// 获取当前 BitBrowser 客户端版本
+///
+/// 从正在运行的进程（或路径缓存）找到可执行文件并读取版本，结果缓存到断连为止
+pub fn current_client_version() -> Option<ClientVersion> {
+    if let Some(cached) = CLIENT_VERSION.read().unwrap().as_ref() {
+        return cached.clone();
+    }
+
+    let exe_path = get_running_bitbrowser_info()
+        .and_then(|info| info.path)
+        .or_else(|| bitbrowser_cache::read().map(|cache| cache.path));
+    let version = exe_path.as_deref().and_then(detect_client_version);
+    if let Some(v) = &version {
+        println!("✓ BitBrowser 客户端版本: {}（来源: {}）", v.version, v.source);
+    }
+
+    *CLIENT_VERSION.write().unwrap() = Some(version.clone());
+    version
+}
+
 /// 测试 API 连接（带重试机制）
-async fn test_api_connection() -> Result<(), ApiError> {
+async fn test_api_connection(base_url: &str) -> Result<(), BitBrowserError> {
     // 最多尝试 3 次
     const MAX_RETRIES: u32 = 3;
     const RETRY_DELAY_MS: u64 = 1000;
@@ -85,7 +149,7 @@ async fn test_api_connection() -> Result<(), ApiError> {
             tokio::time::sleep(std::time::Duration::from_millis(RETRY_DELAY_MS)).await;
         }
 
-        match try_connect_once().await {
+        match try_connect_once(base_url).await {
             Ok(_) => return Ok(()),
             Err(e) => last_error = Some(e),
         }
@@ -96,48 +160,36 @@ async fn test_api_connection() -> Result<(), ApiError> {
 }
 
 /// 单次连接尝试
-async fn try_connect_once() -> Result<(), ApiError> {
-    // 创建 HTTP 客户端（禁用代理以避免 502 错误）
-    let client = reqwest::Client::builder()
-        .no_proxy()  // 关键修复：禁用代理
-        .build()
-        .map_err(|e| ApiError::RequestFailed(e.to_string()))?;
-
-    let request_body = serde_json::json!({
-        "page": 0,
-        "pageSize": 1
-    });
-
-    // 发送请求
-    let response = client
-        .post(format!("{}/browser/list", API_BASE_URL))
-        .json(&request_body)
-        .send()
+async fn try_connect_once(base_url: &str) -> Result<(), BitBrowserError> {
+    // 请求一条记录验证 BitBrowser API 响应结构
+    BitBrowserClient::new(base_url)
+        .list_browsers(&BrowserListRequest::new(0, 1))
         .await
-        .map_err(|e| ApiError::RequestFailed(e.to_string()))?;
+        .map(|_| ())
+}
 
-    // 检查 HTTP 状态码
-    let status = response.status();
-    if !status.is_success() {
-        return Err(ApiError::HttpError(status.as_u16()));
+/// 诊断断连原因
+async fn diagnose_disconnect_reason(base_url: &str, api_error: BitBrowserError) -> ConnectionStatus {
+    // 0. API 已响应但拒绝访问，说明需要（正确的）API Token
+    if let BitBrowserError::HttpError(401) = api_error {
+        return ConnectionStatus::Disconnected {
+            reason: DisconnectReason::Unauthorized,
+            message: "BitBrowser API 鉴权失败，请检查 API Token 设置".to_string(),
+        };
     }
 
-    // 解析并验证响应
-    let json: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| ApiError::ResponseReadFailed(e.to_string()))?;
-
-    // 验证 BitBrowser API 响应结构
-    if json.get("success").is_none() || json.get("data").and_then(|d| d.get("list")).is_none() {
-        return Err(ApiError::InvalidResponse("无效的响应格式".to_string()));
+    // 远程地址无法检查本机进程和端口，直接返回 API 错误
+    if !is_local_api_url(base_url) {
+        return ConnectionStatus::Disconnected {
+            reason: DisconnectReason::ApiError {
+                error: api_error.to_string(),
+            },
+            message: format!("无法连接 BitBrowser API: {}", base_url),
+        };
     }
 
-    Ok(())
-}
+    let api_port = api_port_from_url(base_url).unwrap_or(DEFAULT_API_PORT);
 
-/// 诊断断连原因
-async fn diagnose_disconnect_reason(api_error: ApiError) -> ConnectionStatus {
     // 1. 检查 BitBrowser 进程是否运行
     if !is_bitbrowser_running() {
         return ConnectionStatus::Disconnected {
@@ -147,7 +199,7 @@ async fn diagnose_disconnect_reason(api_error: ApiError) -> ConnectionStatus {
     }
 
     // 2. BitBrowser 进程在运行，检查端口占用情况
-    if let Some(pid) = find_process_using_port(API_PORT) {
+    if let Some(pid) = find_process_using_port(api_port) {
         let mut system = System::new_all();
         system.refresh_processes();
         let sys_pid = sysinfo::Pid::from_u32(pid);
@@ -160,14 +212,14 @@ async fn diagnose_disconnect_reason(api_error: ApiError) -> ConnectionStatus {
                 // BitBrowser 占用端口但 API 无响应
                 // 可能是正在初始化，也可能是 API 错误
                 match api_error {
-                    ApiError::RequestFailed(_) => {
+                    BitBrowserError::RequestFailed(_) => {
                         // 连接超时或拒绝连接 -> 正在初始化
                         return ConnectionStatus::Disconnected {
                             reason: DisconnectReason::Initializing,
                             message: "BitBrowser 正在启动，请稍候...".to_string(),
                         };
                     }
-                    ApiError::HttpError(502) | ApiError::HttpError(503) | ApiError::HttpError(504) => {
+                    BitBrowserError::HttpError(502..=504) => {
                         // 502/503/504 表示服务器暂时不可用，通常是正在初始化
                         return ConnectionStatus::Disconnected {
                             reason: DisconnectReason::Initializing,
@@ -193,7 +245,7 @@ async fn diagnose_disconnect_reason(api_error: ApiError) -> ConnectionStatus {
                     },
                     message: format!(
                         "端口 {} 被进程 {} (PID: {}) 占用",
-                        API_PORT, process_name, pid
+                        api_port, process_name, pid
                     ),
                 };
             }
@@ -210,26 +262,6 @@ async fn diagnose_disconnect_reason(api_error: ApiError) -> ConnectionStatus {
     }
 }
 
-/// API 错误类型
-#[derive(Debug)]
-enum ApiError {
-    RequestFailed(String),
-    HttpError(u16),
-    ResponseReadFailed(String),
-    InvalidResponse(String),
-}
-
-impl std::fmt::Display for ApiError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ApiError::RequestFailed(msg) => write!(f, "请求失败: {}", msg),
-            ApiError::HttpError(code) => write!(f, "HTTP 错误: {}", code),
-            ApiError::ResponseReadFailed(msg) => write!(f, "响应读取失败: {}", msg),
-            ApiError::InvalidResponse(msg) => write!(f, "无效响应: {}", msg),
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
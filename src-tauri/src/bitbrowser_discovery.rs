@@ -0,0 +1,498 @@
+/**
+ * BitBrowser Discovery
+ * BitBrowser 安装路径的查找策略
+ *
+ * 每种查找方式实现 DiscoveryStrategy，find_bitbrowser_path 按当前系统的
+ * 策略列表依次执行，第一个找到的路径即为结果。
+ *
+ * - Windows：运行中的进程 → 注册表 → 常见目录 → 开始菜单
+ * - Linux：运行中的进程 → XDG .desktop → /opt 等目录 → AppImage
+ * - macOS：运行中的进程 → .app 应用包
+ *
+ * 遍历磁盘的深度搜索较慢，不在此列表中，由 bitbrowser_search 作为后台任务执行。
+ */
+#[cfg(any(not(target_os = "windows"), test))]
+use std::fs;
+#[cfg(any(not(target_os = "windows"), test))]
+use std::path::Path;
+#[cfg(not(target_os = "windows"))]
+use std::path::PathBuf;
+
+use crate::bitbrowser_manager;
+
+/// 匹配安装目录、快捷方式和应用名称的关键词（小写）
+#[cfg(any(not(target_os = "windows"), test))]
+const KEYWORDS: &[&str] = &["bitbrowser", "bit browser", "bit-browser", "比特浏览器"];
+
+/// 安装路径查找策略
+pub trait DiscoveryStrategy: Send + Sync {
+    /// 策略标识（记录在路径缓存中）
+    fn id(&self) -> &'static str;
+
+    /// 策略名称（用于日志）
+    fn name(&self) -> &'static str;
+
+    /// 执行查找，返回可执行文件路径
+    fn discover(&self) -> Option<String>;
+}
+
+/// 当前系统的查找策略（按优先级排序）
+pub fn default_strategies() -> Vec<Box<dyn DiscoveryStrategy>> {
+    let mut strategies: Vec<Box<dyn DiscoveryStrategy>> = vec![Box::new(RunningProcess)];
+
+    #[cfg(target_os = "windows")]
+    {
+        strategies.push(Box::new(WindowsRegistry));
+        strategies.push(Box::new(CommonDirectories));
+        strategies.push(Box::new(StartMenu));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        strategies.push(Box::new(XdgDesktopEntries));
+        strategies.push(Box::new(InstallDirectories));
+        strategies.push(Box::new(AppImages));
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        strategies.push(Box::new(AppBundles));
+    }
+
+    strategies
+}
+
+/// 名称是否包含 BitBrowser 关键词
+#[cfg(any(not(target_os = "windows"), test))]
+fn matches_keyword(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    KEYWORDS.iter().any(|k| lower.contains(k))
+}
+
+// ==================== 通用策略 ====================
+
+/// 从正在运行的 BitBrowser 进程获取路径
+pub struct RunningProcess;
+
+impl DiscoveryStrategy for RunningProcess {
+    fn id(&self) -> &'static str {
+        "running_process"
+    }
+
+    fn name(&self) -> &'static str {
+        "正在运行的进程"
+    }
+
+    fn discover(&self) -> Option<String> {
+        bitbrowser_manager::get_running_bitbrowser_info()?.path
+    }
+}
+
+// ==================== Windows ====================
+
+/// 从注册表的卸载信息查找
+#[cfg(target_os = "windows")]
+pub struct WindowsRegistry;
+
+#[cfg(target_os = "windows")]
+impl DiscoveryStrategy for WindowsRegistry {
+    fn id(&self) -> &'static str {
+        "registry"
+    }
+
+    fn name(&self) -> &'static str {
+        "注册表"
+    }
+
+    fn discover(&self) -> Option<String> {
+        bitbrowser_manager::find_bitbrowser_in_registry()
+    }
+}
+
+/// 扫描用户目录和所有盘符的常见安装目录
+#[cfg(target_os = "windows")]
+pub struct CommonDirectories;
+
+#[cfg(target_os = "windows")]
+impl DiscoveryStrategy for CommonDirectories {
+    fn id(&self) -> &'static str {
+        "common_directories"
+    }
+
+    fn name(&self) -> &'static str {
+        "常见安装目录"
+    }
+
+    fn discover(&self) -> Option<String> {
+        bitbrowser_manager::scan_common_directories()
+    }
+}
+
+/// 从开始菜单快捷方式查找
+#[cfg(target_os = "windows")]
+pub struct StartMenu;
+
+#[cfg(target_os = "windows")]
+impl DiscoveryStrategy for StartMenu {
+    fn id(&self) -> &'static str {
+        "start_menu"
+    }
+
+    fn name(&self) -> &'static str {
+        "开始菜单"
+    }
+
+    fn discover(&self) -> Option<String> {
+        bitbrowser_manager::find_in_start_menu()
+    }
+}
+
+// ==================== Linux ====================
+
+/// 从 XDG 应用菜单（.desktop 文件）的 Exec 字段查找
+#[cfg(target_os = "linux")]
+pub struct XdgDesktopEntries;
+
+#[cfg(target_os = "linux")]
+impl XdgDesktopEntries {
+    /// .desktop 文件所在目录（$XDG_DATA_HOME 和 $XDG_DATA_DIRS 下的 applications）
+    fn application_dirs() -> Vec<PathBuf> {
+        let mut data_dirs = Vec::new();
+
+        match std::env::var_os("XDG_DATA_HOME") {
+            Some(dir) if !dir.is_empty() => data_dirs.push(PathBuf::from(dir)),
+            _ => {
+                if let Some(home_dir) = dirs::home_dir() {
+                    data_dirs.push(home_dir.join(".local/share"));
+                }
+            }
+        }
+
+        let system_dirs = std::env::var("XDG_DATA_DIRS")
+            .ok()
+            .filter(|dirs| !dirs.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+        data_dirs.extend(system_dirs.split(':').map(PathBuf::from));
+
+        // Snap 和 Flatpak 导出的应用
+        data_dirs.push(PathBuf::from("/var/lib/snapd/desktop"));
+        data_dirs.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+
+        data_dirs.into_iter().map(|dir| dir.join("applications")).collect()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl DiscoveryStrategy for XdgDesktopEntries {
+    fn id(&self) -> &'static str {
+        "xdg_desktop"
+    }
+
+    fn name(&self) -> &'static str {
+        "XDG 应用菜单"
+    }
+
+    fn discover(&self) -> Option<String> {
+        for dir in Self::application_dirs() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+
+            for entry in entries.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                if path.extension().map_or(true, |e| e != "desktop") {
+                    continue;
+                }
+
+                let content = match fs::read_to_string(&path) {
+                    Ok(content) => content,
+                    Err(_) => continue,
+                };
+
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                if let Some(desktop) = parse_desktop_entry(&content) {
+                    if !matches_keyword(&file_name) && !matches_keyword(&desktop.name) {
+                        continue;
+                    }
+                    if let Some(exe) = resolve_program(&desktop.program) {
+                        return Some(exe);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// 扫描 /opt 等常见安装目录
+#[cfg(target_os = "linux")]
+pub struct InstallDirectories;
+
+#[cfg(target_os = "linux")]
+impl DiscoveryStrategy for InstallDirectories {
+    fn id(&self) -> &'static str {
+        "install_directories"
+    }
+
+    fn name(&self) -> &'static str {
+        "常见安装目录"
+    }
+
+    fn discover(&self) -> Option<String> {
+        let mut roots = vec![
+            PathBuf::from("/opt"),
+            PathBuf::from("/usr/lib"),
+            PathBuf::from("/usr/local/lib"),
+            PathBuf::from("/usr/share"),
+        ];
+        if let Some(home_dir) = dirs::home_dir() {
+            roots.push(home_dir.join(".local/share"));
+            roots.push(home_dir.join("Applications"));
+            roots.push(home_dir);
+        }
+
+        // 根目录下名称包含关键词的子目录
+        for root in roots {
+            let entries = match fs::read_dir(&root) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+
+            for entry in entries.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                if path.is_dir() && matches_keyword(&entry.file_name().to_string_lossy()) {
+                    if let Some(exe) = bitbrowser_manager::find_exe_in_directory(&path) {
+                        return Some(exe);
+                    }
+                }
+            }
+        }
+
+        // 安装到 PATH 中的启动命令
+        for dir in ["/usr/bin", "/usr/local/bin"] {
+            if let Some(exe) = bitbrowser_manager::find_exe_in_directory(&PathBuf::from(dir)) {
+                return Some(exe);
+            }
+        }
+
+        None
+    }
+}
+
+/// 查找 AppImage 文件
+#[cfg(target_os = "linux")]
+pub struct AppImages;
+
+#[cfg(target_os = "linux")]
+impl DiscoveryStrategy for AppImages {
+    fn id(&self) -> &'static str {
+        "appimage"
+    }
+
+    fn name(&self) -> &'static str {
+        "AppImage"
+    }
+
+    fn discover(&self) -> Option<String> {
+        let mut dirs_to_scan = vec![PathBuf::from("/opt")];
+        if let Some(home_dir) = dirs::home_dir() {
+            dirs_to_scan.insert(0, home_dir.join("Applications"));
+            dirs_to_scan.insert(1, home_dir.join("Downloads"));
+            dirs_to_scan.insert(2, home_dir.join("Desktop"));
+            dirs_to_scan.insert(3, home_dir);
+        }
+
+        dirs_to_scan.iter().find_map(|dir| find_appimage_in(dir))
+    }
+}
+
+/// 在目录中查找 BitBrowser 的 AppImage（不递归）
+#[cfg(any(target_os = "linux", test))]
+fn find_appimage_in(dir: &Path) -> Option<String> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .find(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .map_or(false, |n| is_bitbrowser_appimage(&n.to_string_lossy()))
+        })
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+/// 文件名是否是 BitBrowser 的 AppImage
+#[cfg(any(target_os = "linux", test))]
+fn is_bitbrowser_appimage(file_name: &str) -> bool {
+    file_name.to_lowercase().ends_with(".appimage") && matches_keyword(file_name)
+}
+
+/// .desktop 文件中与查找相关的字段
+#[cfg(any(target_os = "linux", test))]
+#[derive(Debug, PartialEq)]
+struct DesktopEntry {
+    name: String,
+    /// Exec 中的程序（已去掉引号和参数）
+    program: String,
+}
+
+/// 解析 .desktop 文件的 [Desktop Entry] 段
+#[cfg(any(target_os = "linux", test))]
+fn parse_desktop_entry(content: &str) -> Option<DesktopEntry> {
+    let mut in_main_section = false;
+    let mut name = String::new();
+    let mut exec = None;
+
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_main_section = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_main_section {
+            continue;
+        }
+
+        if let Some(value) = line.strip_prefix("Name=") {
+            name = value.to_string();
+        } else if let Some(value) = line.strip_prefix("Exec=") {
+            exec = Some(value.to_string());
+        }
+    }
+
+    let exec = exec?;
+    let program = if let Some(quoted) = exec.strip_prefix('"') {
+        quoted.split('"').next()?.to_string()
+    } else {
+        exec.split_whitespace().next()?.to_string()
+    };
+
+    // env 启动的形式：Exec=env VAR=1 /opt/app/bin
+    let program = if program == "env" {
+        exec.split_whitespace()
+            .skip(1)
+            .find(|arg| !arg.contains('='))?
+            .to_string()
+    } else {
+        program
+    };
+
+    Some(DesktopEntry { name, program })
+}
+
+/// 将 Exec 中的程序解析为存在的可执行文件路径（相对名称在 PATH 中查找）
+#[cfg(any(target_os = "linux", test))]
+fn resolve_program(program: &str) -> Option<String> {
+    let path = Path::new(program);
+    if path.is_absolute() {
+        return path.is_file().then(|| program.to_string());
+    }
+
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(program))
+            .find(|candidate| candidate.is_file())
+            .map(|candidate| candidate.to_string_lossy().to_string())
+    })
+}
+
+// ==================== macOS ====================
+
+/// 查找 /Applications 和 ~/Applications 下的 .app 应用包
+#[cfg(target_os = "macos")]
+pub struct AppBundles;
+
+#[cfg(target_os = "macos")]
+impl DiscoveryStrategy for AppBundles {
+    fn id(&self) -> &'static str {
+        "app_bundle"
+    }
+
+    fn name(&self) -> &'static str {
+        ".app 应用包"
+    }
+
+    fn discover(&self) -> Option<String> {
+        let mut roots = vec![PathBuf::from("/Applications")];
+        if let Some(home_dir) = dirs::home_dir() {
+            roots.push(home_dir.join("Applications"));
+        }
+
+        for root in roots {
+            let entries = match fs::read_dir(&root) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+
+            for entry in entries.filter_map(|e| e.ok()) {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.ends_with(".app") && matches_keyword(&name) {
+                    if let Some(exe) = bundle_executable(&entry.path()) {
+                        return Some(exe);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// 获取 .app 应用包中的可执行文件
+#[cfg(target_os = "macos")]
+fn bundle_executable(bundle: &Path) -> Option<String> {
+    let contents = bundle.join("Contents");
+
+    // 优先使用 Info.plist 中声明的可执行文件
+    if let Ok(plist) = fs::read_to_string(contents.join("Info.plist")) {
+        if let Some(name) = plist_bundle_executable(&plist) {
+            let exe = contents.join("MacOS").join(name);
+            if exe.is_file() {
+                return Some(exe.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    bitbrowser_manager::find_exe_in_directory(&contents.join("MacOS"))
+}
+
+/// 从 XML 格式的 Info.plist 中读取 CFBundleExecutable
+#[cfg(any(target_os = "macos", test))]
+fn plist_bundle_executable(plist: &str) -> Option<String> {
+    crate::bitbrowser_version::plist_string(plist, "CFBundleExecutable")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_desktop_entry() {
+        let content = "[Desktop Entry]\nName=BitBrowser\nExec=\"/opt/BitBrowser/bitbrowser\" --no-sandbox %U\n\n[Desktop Action new]\nExec=/usr/bin/other\n";
+        assert_eq!(
+            parse_desktop_entry(content),
+            Some(DesktopEntry {
+                name: "BitBrowser".to_string(),
+                program: "/opt/BitBrowser/bitbrowser".to_string(),
+            })
+        );
+
+        let content = "[Desktop Entry]\nName=比特浏览器\nExec=env LANG=zh_CN.UTF-8 /opt/比特浏览器/bitbrowser %U\n";
+        assert_eq!(parse_desktop_entry(content).unwrap().program, "/opt/比特浏览器/bitbrowser");
+
+        assert_eq!(parse_desktop_entry("[Desktop Entry]\nName=NoExec\n"), None);
+    }
+
+    #[test]
+    fn test_appimage_and_plist_matching() {
+        assert!(is_bitbrowser_appimage("BitBrowser-4.2.1-x86_64.AppImage"));
+        assert!(!is_bitbrowser_appimage("BitBrowser.deb"));
+        assert!(!is_bitbrowser_appimage("Other.AppImage"));
+
+        let plist = "<dict>\n\t<key>CFBundleExecutable</key>\n\t<string>BitBrowser</string>\n</dict>";
+        assert_eq!(plist_bundle_executable(plist).as_deref(), Some("BitBrowser"));
+        assert_eq!(plist_bundle_executable("<dict></dict>"), None);
+    }
+}
@@ -0,0 +1,247 @@
+/**
+ * BitBrowser History
+ * 记录 BitBrowser 连接状态的变化历史
+ *
+ * 功能：
+ * - 有界环形缓冲区保存状态变化（原因、起止时间、持续时长）
+ * - 统计在线时长、断连次数和最长断连时长
+ * - 判断连接是否频繁抖动
+ */
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::bitbrowser_detector::DisconnectReason;
+
+/// 默认保留的状态变化条数
+pub const DEFAULT_HISTORY_CAPACITY: usize = 200;
+
+/// 抖动判定窗口（毫秒）
+const FLAP_WINDOW_MS: u64 = 10 * 60 * 1000;
+
+/// 窗口内连接/断开切换达到该次数视为抖动
+const FLAP_THRESHOLD: usize = 4;
+
+/// 一段连续的连接状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusTransition {
+    pub connected: bool,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<DisconnectReason>,
+    /// 进入该状态的时间戳（毫秒）
+    pub started_at: u64,
+    /// 离开该状态的时间戳（毫秒），当前状态为 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<u64>,
+    /// 持续时长（毫秒），当前状态为 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+/// 连接统计
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionStats {
+    /// 统计覆盖的时长（毫秒，从最早一条记录到现在）
+    pub observed_ms: u64,
+    pub uptime_ms: u64,
+    pub downtime_ms: u64,
+    /// 在线比例（0.0 - 1.0）
+    pub uptime_ratio: f64,
+    /// 断连次数（连续的断连状态算一次，断连原因变化不单独计数）
+    pub outage_count: usize,
+    /// 最长一次断连（毫秒，包含其中所有断连原因的时长）
+    pub longest_outage_ms: u64,
+    /// 最近一次断连开始时间（毫秒）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_outage_at: Option<u64>,
+    /// 各断连原因出现的次数
+    pub outages_by_reason: BTreeMap<String, usize>,
+    /// 最近 10 分钟内连接/断开的切换次数
+    pub recent_transitions: usize,
+    /// 是否频繁抖动
+    pub flapping: bool,
+}
+
+/// 连接状态历史（有界环形缓冲区）
+#[derive(Debug)]
+pub struct ConnectionHistory {
+    entries: VecDeque<StatusTransition>,
+    capacity: usize,
+}
+
+impl ConnectionHistory {
+    pub fn new(capacity: usize) -> Self {
+        ConnectionHistory {
+            entries: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// 记录一次状态变化：结束当前状态并开始新状态
+    pub fn push(
+        &mut self,
+        connected: bool,
+        message: String,
+        reason: Option<DisconnectReason>,
+        timestamp: u64,
+    ) {
+        if let Some(last) = self.entries.back_mut() {
+            if last.ended_at.is_none() {
+                last.ended_at = Some(timestamp);
+                last.duration_ms = Some(timestamp.saturating_sub(last.started_at));
+            }
+        }
+
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(StatusTransition {
+            connected,
+            message,
+            reason,
+            started_at: timestamp,
+            ended_at: None,
+            duration_ms: None,
+        });
+    }
+
+    /// 全部历史记录（从旧到新）
+    pub fn entries(&self) -> Vec<StatusTransition> {
+        self.entries.iter().cloned().collect()
+    }
+
+    /// 计算统计信息
+    pub fn stats(&self, now: u64) -> ConnectionStats {
+        let mut stats = ConnectionStats::default();
+
+        let first = match self.entries.front() {
+            Some(first) => first,
+            None => return stats,
+        };
+        stats.observed_ms = now.saturating_sub(first.started_at);
+
+        // 当前这次断连的累计时长（连续的断连记录合并为一次）
+        let mut outage_ms = 0;
+        let mut previous_connected = None;
+        for entry in &self.entries {
+            let duration = entry
+                .duration_ms
+                .unwrap_or_else(|| now.saturating_sub(entry.started_at));
+
+            if entry.connected {
+                stats.uptime_ms += duration;
+                previous_connected = Some(true);
+                continue;
+            }
+
+            stats.downtime_ms += duration;
+            if previous_connected == Some(false) {
+                outage_ms += duration;
+            } else {
+                stats.outage_count += 1;
+                stats.last_outage_at = Some(entry.started_at);
+                outage_ms = duration;
+            }
+            stats.longest_outage_ms = stats.longest_outage_ms.max(outage_ms);
+            previous_connected = Some(false);
+
+            let kind = entry.reason.as_ref().map_or("unknown", |r| r.kind());
+            *stats.outages_by_reason.entry(kind.to_string()).or_insert(0) += 1;
+        }
+
+        let total = stats.uptime_ms + stats.downtime_ms;
+        if total > 0 {
+            stats.uptime_ratio = stats.uptime_ms as f64 / total as f64;
+        }
+
+        // 只统计连接/断开的切换，断连原因的变化（如未运行 → 初始化中）不算抖动
+        stats.recent_transitions = self
+            .entries
+            .iter()
+            .zip(self.entries.iter().skip(1))
+            .filter(|(previous, entry)| {
+                previous.connected != entry.connected
+                    && now.saturating_sub(entry.started_at) <= FLAP_WINDOW_MS
+            })
+            .count();
+        stats.flapping = stats.recent_transitions >= FLAP_THRESHOLD;
+
+        stats
+    }
+}
+
+impl Default for ConnectionHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIN: u64 = 60 * 1000;
+
+    #[test]
+    fn test_durations_and_stats() {
+        let mut history = ConnectionHistory::default();
+        history.push(false, "未运行".into(), Some(DisconnectReason::NotRunning), 0);
+        history.push(false, "初始化".into(), Some(DisconnectReason::Initializing), MIN);
+        history.push(true, "已连接".into(), None, 2 * MIN);
+
+        let entries = history.entries();
+        assert_eq!(entries[0].duration_ms, Some(MIN));
+        assert_eq!(entries[2].ended_at, None);
+
+        let stats = history.stats(10 * MIN);
+        assert_eq!(stats.observed_ms, 10 * MIN);
+        assert_eq!(stats.downtime_ms, 2 * MIN);
+        assert_eq!(stats.uptime_ms, 8 * MIN);
+        // 冷启动（未运行 → 初始化中 → 已连接）是一次断连
+        assert_eq!(stats.outage_count, 1);
+        assert_eq!(stats.longest_outage_ms, 2 * MIN);
+        assert_eq!(stats.last_outage_at, Some(0));
+        assert_eq!(stats.outages_by_reason["not_running"], 1);
+        assert_eq!(stats.outages_by_reason["initializing"], 1);
+        assert!((stats.uptime_ratio - 0.8).abs() < 1e-9);
+        assert_eq!(stats.recent_transitions, 1);
+        assert!(!stats.flapping);
+
+        // 再次断连后重启
+        history.push(false, "未运行".into(), Some(DisconnectReason::NotRunning), 10 * MIN);
+        history.push(false, "初始化".into(), Some(DisconnectReason::Initializing), 11 * MIN);
+        history.push(false, "端口占用".into(), None, 12 * MIN);
+        history.push(true, "已连接".into(), None, 15 * MIN);
+
+        let stats = history.stats(16 * MIN);
+        assert_eq!(stats.outage_count, 2);
+        assert_eq!(stats.longest_outage_ms, 5 * MIN);
+        assert_eq!(stats.last_outage_at, Some(10 * MIN));
+        assert_eq!(stats.recent_transitions, 2);
+        assert!(!stats.flapping);
+    }
+
+    #[test]
+    fn test_capacity_and_flapping() {
+        let mut history = ConnectionHistory::new(3);
+        for i in 0..6u64 {
+            let connected = i % 2 == 0;
+            let reason = if connected { None } else { Some(DisconnectReason::NotRunning) };
+            history.push(connected, String::new(), reason, i * MIN);
+        }
+
+        let entries = history.entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].started_at, 3 * MIN);
+
+        let mut history = ConnectionHistory::default();
+        for i in 0..6u64 {
+            history.push(i % 2 == 0, String::new(), None, i * MIN);
+        }
+        assert!(history.stats(6 * MIN).flapping);
+        assert!(!history.stats(60 * MIN).flapping);
+    }
+}
@@ -0,0 +1,300 @@
+/**
+ * BitBrowser Launch Profile
+ * BitBrowser 启动配置（保存在 settings.json 的 bitbrowser_launch 中）
+ *
+ * 功能：
+ * - 额外启动参数、工作目录、环境变量
+ * - 窗口模式：普通 / 最小化 / 隐藏
+ *   - Windows：通过 PowerShell Start-Process -WindowStyle 启动（PowerShell 本身不显示控制台窗口），
+ *     PowerShell 输出 BitBrowser 的 PID；这种方式下无法捕获 BitBrowser 的标准错误
+ *   - Linux：隐藏模式在 xvfb-run 虚拟显示中启动（用于无桌面的机器）
+ *   - 其他系统不支持，按普通方式启动
+ * - 分离进程：不随本程序退出或接收本程序的控制台信号
+ */
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::RwLock;
+
+/// 当前生效的启动配置
+static LAUNCH_PROFILE: RwLock<Option<LaunchProfile>> = RwLock::new(None);
+
+/// 窗口模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowMode {
+    #[default]
+    Normal,
+    Minimized,
+    Hidden,
+}
+
+/// 启动配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LaunchProfile {
+    /// 额外启动参数
+    pub args: Vec<String>,
+    /// 工作目录，None 时继承本程序的工作目录
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+    /// 额外环境变量
+    pub env: HashMap<String, String>,
+    pub window: WindowMode,
+    /// 以分离进程启动
+    pub detached: bool,
+}
+
+impl LaunchProfile {
+    /// 校验配置
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(dir) = &self.working_dir {
+            if !Path::new(dir).is_dir() {
+                return Err(format!("工作目录不存在: {}", dir));
+            }
+        }
+
+        for key in self.env.keys() {
+            if key.is_empty() || key.contains('=') || key.contains('\0') {
+                return Err(format!("无效的环境变量名: {:?}", key));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// 设置启动配置（None 表示使用默认配置）
+pub fn set_launch_profile(profile: Option<LaunchProfile>) {
+    if let Some(profile) = &profile {
+        println!(
+            "✓ BitBrowser 启动配置: {} 个参数，窗口模式 {:?}，分离进程 {}",
+            profile.args.len(),
+            profile.window,
+            profile.detached
+        );
+    }
+    *LAUNCH_PROFILE.write().unwrap() = profile;
+}
+
+/// 获取当前启动配置
+pub fn launch_profile() -> LaunchProfile {
+    LAUNCH_PROFILE.read().unwrap().clone().unwrap_or_default()
+}
+
+/// 按启动配置构建启动命令
+///
+/// Windows 的最小化/隐藏模式和 Linux 的隐藏模式会通过中间程序启动，
+/// 此时返回的命令对应中间程序而不是 BitBrowser 本身。
+/// Windows 下中间程序会在标准输出打印 BitBrowser 的 PID（见 prints_started_pid）。
+pub fn build_command(exe_path: &str, profile: &LaunchProfile) -> Command {
+    let mut command = platform_command(exe_path, profile);
+
+    if let Some(dir) = &profile.working_dir {
+        command.current_dir(dir);
+    }
+    command.envs(&profile.env);
+
+    if profile.detached {
+        detach(&mut command);
+    }
+
+    command
+}
+
+/// 启动命令是否通过 Start-Process 启动并在标准输出打印 BitBrowser 的 PID
+///
+/// 此时子进程是 PowerShell：它的退出状态和标准错误都不属于 BitBrowser。
+pub fn prints_started_pid(profile: &LaunchProfile) -> bool {
+    cfg!(target_os = "windows") && profile.window != WindowMode::Normal
+}
+
+#[cfg(target_os = "windows")]
+fn platform_command(exe_path: &str, profile: &LaunchProfile) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    /// 不为 PowerShell 创建控制台窗口（分离进程时 DETACHED_PROCESS 同样不会创建）
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let window_style = match profile.window {
+        WindowMode::Normal => {
+            let mut command = Command::new(exe_path);
+            command.args(&profile.args);
+            return command;
+        }
+        WindowMode::Minimized => "Minimized",
+        WindowMode::Hidden => "Hidden",
+    };
+
+    let mut command = Command::new("powershell");
+    command
+        .args(["-NoProfile", "-NonInteractive", "-Command"])
+        .arg(start_process_script(exe_path, &profile.args, window_style))
+        .creation_flags(CREATE_NO_WINDOW);
+    command
+}
+
+#[cfg(target_os = "linux")]
+fn platform_command(exe_path: &str, profile: &LaunchProfile) -> Command {
+    let xvfb_run = match profile.window {
+        WindowMode::Hidden => find_in_path("xvfb-run"),
+        WindowMode::Minimized => {
+            println!("⚠ Linux 不支持最小化启动，按普通方式启动");
+            None
+        }
+        WindowMode::Normal => None,
+    };
+
+    match xvfb_run {
+        Some(xvfb_run) => {
+            let mut command = Command::new(xvfb_run);
+            command.arg("-a").arg(exe_path).args(&profile.args);
+            command
+        }
+        None => {
+            if profile.window == WindowMode::Hidden {
+                println!("⚠ 未找到 xvfb-run，无法隐藏启动，按普通方式启动");
+            }
+            let mut command = Command::new(exe_path);
+            command.args(&profile.args);
+            command
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn platform_command(exe_path: &str, profile: &LaunchProfile) -> Command {
+    if profile.window != WindowMode::Normal {
+        println!("⚠ 当前系统不支持隐藏/最小化启动，按普通方式启动");
+    }
+    let mut command = Command::new(exe_path);
+    command.args(&profile.args);
+    command
+}
+
+/// 在 PATH 中查找可执行文件
+#[cfg(target_os = "linux")]
+fn find_in_path(name: &str) -> Option<std::path::PathBuf> {
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// 分离进程：不共享控制台，不接收本程序的 Ctrl+C
+#[cfg(target_os = "windows")]
+fn detach(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+
+    const DETACHED_PROCESS: u32 = 0x0000_0008;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+/// 分离进程：放入新的进程组，不接收终端发给本程序的信号
+#[cfg(unix)]
+fn detach(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+
+    command.process_group(0);
+}
+
+#[cfg(not(any(target_os = "windows", unix)))]
+fn detach(_command: &mut Command) {}
+
+/// 生成 Start-Process 脚本（参数按 Windows 命令行规则拼接），脚本输出 BitBrowser 的 PID
+#[cfg(any(target_os = "windows", test))]
+fn start_process_script(exe_path: &str, args: &[String], window_style: &str) -> String {
+    let mut script = format!(
+        "(Start-Process -FilePath {} -WindowStyle {}",
+        powershell_quote(exe_path),
+        window_style
+    );
+    if !args.is_empty() {
+        let arg_line = args
+            .iter()
+            .map(|arg| windows_quote(arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+        script.push_str(&format!(" -ArgumentList {}", powershell_quote(&arg_line)));
+    }
+    script.push_str(" -PassThru).Id");
+    script
+}
+
+/// PowerShell 单引号字符串
+#[cfg(any(target_os = "windows", test))]
+fn powershell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// 按 Windows 命令行规则给参数加引号（仅在包含空白或引号时）
+#[cfg(any(target_os = "windows", test))]
+fn windows_quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || c == '"') {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_defaults_and_validation() {
+        let profile: LaunchProfile = serde_json::from_str(r#"{"window": "hidden"}"#).unwrap();
+        assert_eq!(profile.window, WindowMode::Hidden);
+        assert!(profile.args.is_empty());
+        assert!(!profile.detached);
+        assert!(profile.validate().is_ok());
+
+        let profile = LaunchProfile {
+            env: HashMap::from([("A=B".to_string(), "1".to_string())]),
+            ..Default::default()
+        };
+        assert!(profile.validate().is_err());
+
+        let profile = LaunchProfile {
+            working_dir: Some("/definitely/not/a/dir".to_string()),
+            ..Default::default()
+        };
+        assert!(profile.validate().is_err());
+    }
+
+    #[test]
+    fn test_start_process_script() {
+        let args = vec!["--port=54345".to_string(), "C:\\My Data\\".to_string()];
+        assert_eq!(
+            start_process_script("C:\\Program Files\\BitBrowser\\BitBrowser.exe", &args, "Hidden"),
+            "(Start-Process -FilePath 'C:\\Program Files\\BitBrowser\\BitBrowser.exe' -WindowStyle Hidden \
+             -ArgumentList '--port=54345 \"C:\\My Data\\\\\"' -PassThru).Id"
+        );
+        assert_eq!(
+            start_process_script("BitBrowser.exe", &[], "Minimized"),
+            "(Start-Process -FilePath 'BitBrowser.exe' -WindowStyle Minimized -PassThru).Id"
+        );
+        assert_eq!(windows_quote("it's \"x\""), "\"it's \\\"x\\\"\"");
+        assert_eq!(powershell_quote("it's"), "'it''s'");
+    }
+}
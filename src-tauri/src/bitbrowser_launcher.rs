@@ -5,21 +5,167 @@
  * 功能：
  * - 自动查找 BitBrowser 路径（沿用现有算法）
  * - 启动 BitBrowser 进程
+ * - launch_and_wait：启动后跟踪子进程并轮询连接状态，直到 API 就绪或超时，
+ *   并向前端推送阶段事件（spawned → initializing → ready / failed）
+ *   - Windows 最小化/隐藏模式通过 PowerShell 启动，报告的是其输出的 BitBrowser PID，
+ *     此时无法捕获 BitBrowser 的标准错误
  */
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader};
+use std::process::Child;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::Manager;
 
+use crate::bitbrowser_detector::{check_status, ConnectionStatus};
 use crate::bitbrowser_manager::{
     find_bitbrowser_path,
     start_bitbrowser,
+    spawn_bitbrowser,
     is_bitbrowser_running,
 };
 
+/// 前端事件名称
+const EVENT_NAME: &str = "bitbrowser-launch";
+
+/// 失败时附带的标准错误行数
+const STDERR_TAIL_LINES: usize = 20;
+
 /// 启动结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LaunchResult {
     pub success: bool,
     pub message: String,
+    /// 启动的子进程 PID（launch_and_wait）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    /// 常规位置未找到 BitBrowser，需要调用方启动后台深度搜索（start_bitbrowser_search）
+    #[serde(default)]
+    pub needs_search: bool,
+}
+
+/// 等待就绪的选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WaitOptions {
+    /// 等待 API 就绪的最长时间（秒）
+    pub timeout_secs: u64,
+    /// 轮询连接状态的间隔（毫秒）
+    pub poll_interval_ms: u64,
+}
+
+impl Default for WaitOptions {
+    fn default() -> Self {
+        WaitOptions {
+            timeout_secs: 90,
+            poll_interval_ms: 1000,
+        }
+    }
+}
+
+/// 启动阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchPhase {
+    /// 进程已启动
+    Spawned,
+    /// 进程运行中，API 尚未就绪
+    Initializing,
+    /// API 已就绪
+    Ready,
+    /// 启动失败（进程退出或超时）
+    Failed,
+}
+
+/// 前端事件 Payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchEvent {
+    pub phase: LaunchPhase,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    pub elapsed_ms: u64,
+    /// 失败时附带的标准错误末尾几行（Windows 最小化/隐藏模式下来自 PowerShell）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
+    pub timestamp: u64,
+}
+
+/// 子进程标准错误的末尾几行
+#[derive(Debug, Default)]
+struct StderrTail {
+    lines: VecDeque<String>,
+}
+
+impl StderrTail {
+    fn push(&mut self, line: String) {
+        if self.lines.len() == STDERR_TAIL_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    /// 拼接后的内容，没有输出时为 None
+    fn excerpt(&self) -> Option<String> {
+        if self.lines.is_empty() {
+            None
+        } else {
+            Some(self.lines.iter().cloned().collect::<Vec<_>>().join("\n"))
+        }
+    }
+}
+
+/// 在后台线程持续读取子进程的标准错误（同时避免管道写满阻塞子进程）
+fn capture_stderr(child: &mut Child) -> Arc<Mutex<StderrTail>> {
+    let tail = Arc::new(Mutex::new(StderrTail::default()));
+    if let Some(stderr) = child.stderr.take() {
+        let tail = tail.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                tail.lock().unwrap().push(line);
+            }
+        });
+    }
+    tail
+}
+
+/// 读取 Start-Process 输出的 BitBrowser PID（没有管道输出时为 None）
+async fn read_started_pid(child: &mut Child) -> Option<u32> {
+    let stdout = child.stdout.take()?;
+    tokio::task::spawn_blocking(move || {
+        let mut line = String::new();
+        BufReader::new(stdout).read_line(&mut line).ok()?;
+        line.trim().parse().ok()
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// 确定 BitBrowser 路径（未指定时自动查找）
+fn resolve_exe_path(path: Option<String>) -> Result<String, String> {
+    match path {
+        Some(p) => {
+            println!("使用指定路径: {}", p);
+            Ok(p)
+        }
+        None => {
+            println!("自动查找 BitBrowser 路径...");
+            match find_bitbrowser_path() {
+                Some(p) => {
+                    println!("✓ 找到路径: {}", p);
+                    Ok(p)
+                }
+                None => {
+                    println!("✗ 未找到 BitBrowser 安装路径");
+                    Err("未找到 BitBrowser 安装路径，请使用深度搜索或手动指定".to_string())
+                }
+            }
+        }
+    }
 }
 
 /// 启动 BitBrowser
@@ -36,32 +182,23 @@ pub async fn launch(path: Option<String>) -> LaunchResult {
         return LaunchResult {
             success: true,
             message: "BitBrowser 已经在运行，请稍候连接就绪".to_string(),
+            pid: None,
+            needs_search: false,
         };
     }
 
     println!("开始启动 BitBrowser...");
 
     // 2. 确定 BitBrowser 路径
-    let exe_path = match path {
-        Some(p) => {
-            println!("使用指定路径: {}", p);
-            p
-        }
-        None => {
-            println!("自动查找 BitBrowser 路径...");
-            match find_bitbrowser_path() {
-                Some(p) => {
-                    println!("✓ 找到路径: {}", p);
-                    p
-                }
-                None => {
-                    println!("✗ 未找到 BitBrowser 安装路径");
-                    return LaunchResult {
-                        success: false,
-                        message: "未找到 BitBrowser 安装路径，请手动指定".to_string(),
-                    };
-                }
-            }
+    let exe_path = match resolve_exe_path(path) {
+        Ok(p) => p,
+        Err(message) => {
+            return LaunchResult {
+                success: false,
+                message,
+                pid: None,
+                needs_search: true,
+            };
         }
     };
 
@@ -73,6 +210,8 @@ pub async fn launch(path: Option<String>) -> LaunchResult {
             LaunchResult {
                 success: true,
                 message: "BitBrowser 正在启动，请稍候 20-30 秒...".to_string(),
+                pid: None,
+                needs_search: false,
             }
         }
         Err(e) => {
@@ -80,11 +219,138 @@ pub async fn launch(path: Option<String>) -> LaunchResult {
             LaunchResult {
                 success: false,
                 message: format!("启动失败：{}", e),
+                pid: None,
+                needs_search: false,
             }
         }
     }
 }
 
+/// 启动 BitBrowser 并等待 API 就绪，向前端推送阶段事件
+pub async fn launch_and_wait(
+    app_handle: &tauri::AppHandle,
+    path: Option<String>,
+    options: &WaitOptions,
+) -> LaunchResult {
+    launch_and_wait_with(path, options, |event| {
+        if let Err(e) = app_handle.emit_all(EVENT_NAME, &event) {
+            eprintln!("⚠ 推送启动事件失败: {}", e);
+        }
+    })
+    .await
+}
+
+/// launch_and_wait 的实现，事件通过 emit 回调推送
+async fn launch_and_wait_with(
+    path: Option<String>,
+    options: &WaitOptions,
+    emit: impl Fn(LaunchEvent),
+) -> LaunchResult {
+    let started = Instant::now();
+    let event = |phase: LaunchPhase, message: &str, pid: Option<u32>, stderr: Option<String>| {
+        emit(LaunchEvent {
+            phase,
+            message: message.to_string(),
+            pid,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            stderr,
+            timestamp: get_timestamp(),
+        })
+    };
+    let finish = |success: bool, message: String, pid: Option<u32>, stderr: Option<String>| {
+        let phase = if success { LaunchPhase::Ready } else { LaunchPhase::Failed };
+        event(phase, &message, pid, stderr);
+        LaunchResult {
+            success,
+            message,
+            pid,
+            needs_search: false,
+        }
+    };
+
+    // 1. 已在运行时只等待就绪，否则启动新进程
+    let mut child = None;
+    let mut pid = None;
+    let mut stderr_tail = Arc::new(Mutex::new(StderrTail::default()));
+    if is_bitbrowser_running() {
+        println!("⚠ BitBrowser 进程已在运行，等待 API 就绪");
+    } else {
+        println!("开始启动 BitBrowser...");
+        let exe_path = match resolve_exe_path(path) {
+            Ok(p) => p,
+            Err(message) => {
+                return LaunchResult {
+                    needs_search: true,
+                    ..finish(false, message, None, None)
+                };
+            }
+        };
+
+        let mut spawned = match spawn_bitbrowser(&exe_path, true) {
+            Ok(c) => c,
+            Err(e) => return finish(false, format!("启动失败：{}", e), None, None),
+        };
+        stderr_tail = capture_stderr(&mut spawned);
+        let started_pid = read_started_pid(&mut spawned).await.unwrap_or_else(|| spawned.id());
+        println!("✓ BitBrowser 已启动 (PID {})", started_pid);
+        event(LaunchPhase::Spawned, "BitBrowser 进程已启动", Some(started_pid), None);
+        pid = Some(started_pid);
+        child = Some(spawned);
+    }
+
+    // 2. 轮询连接状态直到就绪、进程退出或超时
+    let deadline = started + Duration::from_secs(options.timeout_secs);
+    let mut initializing_reported = false;
+    loop {
+        if let Some(c) = child.as_mut() {
+            if let Ok(Some(status)) = c.try_wait() {
+                // 启动器进程可能拉起主进程后自行退出
+                if status.success() && is_bitbrowser_running() {
+                    println!("BitBrowser 启动进程已退出，继续等待主进程就绪");
+                    child = None;
+                } else {
+                    let excerpt = stderr_tail.lock().unwrap().excerpt();
+                    println!("✗ BitBrowser 进程已退出: {}", status);
+                    return finish(false, format!("BitBrowser 进程已退出（{}）", status), pid, excerpt);
+                }
+            }
+        }
+
+        match check_status().await {
+            ConnectionStatus::Connected { message, .. } => {
+                println!("✓ BitBrowser API 已就绪，用时 {} 秒", started.elapsed().as_secs());
+                return finish(true, message, pid, None);
+            }
+            ConnectionStatus::Disconnected { message, .. } => {
+                if !initializing_reported {
+                    initializing_reported = true;
+                    event(LaunchPhase::Initializing, &message, pid, None);
+                }
+            }
+        }
+
+        if Instant::now() >= deadline {
+            let excerpt = stderr_tail.lock().unwrap().excerpt();
+            println!("✗ 等待 BitBrowser 就绪超时（{} 秒）", options.timeout_secs);
+            return finish(
+                false,
+                format!("等待 BitBrowser 就绪超时（{} 秒）", options.timeout_secs),
+                pid,
+                excerpt,
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(options.poll_interval_ms)).await;
+    }
+}
+
+/// 获取当前时间戳（毫秒）
+fn get_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,6 +359,53 @@ mod tests {
     async fn test_launch() {
         let result = launch(None).await;
         println!("启动结果: {:?}", result);
-        assert!(result.success || result.message.contains("已经在运行") || result.message.contains("未找到"));
+        assert!(result.success || result.message.contains("已经在运行") || result.needs_search);
+    }
+
+    #[test]
+    fn test_stderr_tail_keeps_last_lines() {
+        let mut tail = StderrTail::default();
+        assert_eq!(tail.excerpt(), None);
+        for i in 0..STDERR_TAIL_LINES + 5 {
+            tail.push(format!("line {}", i));
+        }
+        let excerpt = tail.excerpt().unwrap();
+        assert_eq!(excerpt.lines().count(), STDERR_TAIL_LINES);
+        assert!(excerpt.starts_with("line 5\n"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_launch_and_wait_reports_early_exit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        if is_bitbrowser_running() {
+            return;
+        }
+
+        let script = std::env::temp_dir().join(format!("bb-launch-{}.sh", std::process::id()));
+        std::fs::write(&script, "#!/bin/sh\necho 'missing libgtk' >&2\nexit 3\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let events = Mutex::new(Vec::new());
+        let options = WaitOptions {
+            timeout_secs: 30,
+            poll_interval_ms: 100,
+        };
+        let result = launch_and_wait_with(
+            Some(script.to_string_lossy().to_string()),
+            &options,
+            |event| events.lock().unwrap().push(event),
+        )
+        .await;
+        std::fs::remove_file(&script).unwrap();
+
+        assert!(!result.success);
+        assert!(result.pid.is_some());
+        let events = events.into_inner().unwrap();
+        assert_eq!(events.first().unwrap().phase, LaunchPhase::Spawned);
+        let last = events.last().unwrap();
+        assert_eq!(last.phase, LaunchPhase::Failed);
+        assert_eq!(last.stderr.as_deref(), Some("missing libgtk"));
     }
 }
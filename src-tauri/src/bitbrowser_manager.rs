@@ -3,14 +3,21 @@
  * 负责查找、启动和管理 BitBrowser 客户端
  */
 use serde::{Deserialize, Serialize};
+#[cfg(target_os = "windows")]
 use std::fs;
-use std::io::{Read, Write};
 use std::path::PathBuf;
-use std::process::Command;
-use std::time::{Duration, Instant};
+use std::process::{Child, Stdio};
+use std::sync::RwLock;
+use std::time::Duration;
 use sysinfo::System;
 
+use crate::bitbrowser_cache;
+use crate::bitbrowser_discovery::default_strategies;
+use crate::bitbrowser_launch_profile::{build_command, launch_profile, prints_started_pid};
+use crate::bitbrowser_version::detect_client_version;
+
 /// 可能的 BitBrowser 可执行文件名
+#[cfg(target_os = "windows")]
 const POSSIBLE_EXE_NAMES: &[&str] = &[
     "BitBrowser.exe",
     "比特浏览器.exe",
@@ -19,11 +26,9 @@ const POSSIBLE_EXE_NAMES: &[&str] = &[
     "BitBrowserClient.exe",
 ];
 
-/// 缓存文件名
-const CACHE_FILE_NAME: &str = "bitbrowser_path_cache.txt";
-
-/// 深度搜索超时时间（秒）
-const DEEP_SEARCH_TIMEOUT_SECS: u64 = 60;
+/// 可能的 BitBrowser 可执行文件名（Linux / macOS）
+#[cfg(not(target_os = "windows"))]
+const POSSIBLE_EXE_NAMES: &[&str] = &["BitBrowser", "bitbrowser", "比特浏览器", "bit-browser"];
 
 #[cfg(target_os = "windows")]
 use winreg::enums::*;
@@ -36,13 +41,16 @@ pub struct BitBrowserInfo {
     pub path: Option<String>,
     pub is_running: bool,
     pub process_id: Option<u32>,
+    /// 路径查找缓存（路径来源、文件信息、版本）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discovery: Option<bitbrowser_cache::DiscoveryCache>,
 }
 
 /// 检查目录中是否存在 BitBrowser 可执行文件（支持多种文件名）
-fn find_exe_in_directory(dir: &PathBuf) -> Option<String> {
+pub(crate) fn find_exe_in_directory(dir: &PathBuf) -> Option<String> {
     for exe_name in POSSIBLE_EXE_NAMES {
         let exe_path = dir.join(exe_name);
-        if exe_path.exists() {
+        if exe_path.is_file() {
             return Some(exe_path.to_string_lossy().to_string());
         }
     }
@@ -90,6 +98,7 @@ pub fn get_running_bitbrowser_info() -> Option<BitBrowserInfo> {
                 path,
                 is_running: true,
                 process_id: Some(pid.as_u32()),
+                discovery: None,
             });
         }
     }
@@ -150,14 +159,9 @@ pub fn find_bitbrowser_in_registry() -> Option<String> {
     None
 }
 
-#[cfg(not(target_os = "windows"))]
-pub fn find_bitbrowser_in_registry() -> Option<String> {
-    None
-}
-
 /// 获取所有可用的逻辑盘符
 #[cfg(target_os = "windows")]
-fn get_available_drives() -> Vec<String> {
+pub(crate) fn get_available_drives() -> Vec<String> {
     let mut drives = Vec::new();
     for letter in b'A'..=b'Z' {
         let drive = format!("{}:\\", letter as char);
@@ -170,11 +174,12 @@ fn get_available_drives() -> Vec<String> {
 }
 
 #[cfg(not(target_os = "windows"))]
-fn get_available_drives() -> Vec<String> {
+pub(crate) fn get_available_drives() -> Vec<String> {
     vec!["/".to_string()]
 }
 
 /// 扫描常见安装目录（所有盘符）
+#[cfg(target_os = "windows")]
 pub fn scan_common_directories() -> Option<String> {
     // 优先检查用户目录
     if let Some(home_dir) = dirs::home_dir() {
@@ -259,6 +264,25 @@ pub fn find_in_start_menu() -> Option<String> {
     None
 }
 
+/// 解析快捷方式指向的 BitBrowser 可执行文件（跳过卸载程序等其他快捷方式）
+#[cfg(target_os = "windows")]
+fn resolve_shortcut_exe(lnk_path: &PathBuf) -> Option<String> {
+    let target = PathBuf::from(crate::shell_link::resolve_lnk_target(lnk_path)?);
+
+    // 优先使用目标所在目录中的主程序
+    if let Some(exe_path) = target.parent().and_then(|dir| find_exe_in_directory(&dir.to_path_buf())) {
+        return Some(exe_path);
+    }
+
+    let file_name = target.file_name()?.to_string_lossy().to_lowercase();
+    let is_uninstaller = file_name.contains("uninst") || file_name.contains("卸载");
+    if target.is_file() && file_name.ends_with(".exe") && !is_uninstaller {
+        return Some(target.to_string_lossy().to_string());
+    }
+
+    None
+}
+
 #[cfg(target_os = "windows")]
 fn search_lnk_files(dir: &PathBuf, keyword: &str) -> Option<String> {
     if let Ok(entries) = fs::read_dir(dir) {
@@ -273,10 +297,11 @@ fn search_lnk_files(dir: &PathBuf, keyword: &str) -> Option<String> {
             } else if path.extension().map_or(false, |e| e == "lnk") {
                 if let Some(file_name) = path.file_name() {
                     if file_name.to_string_lossy().to_lowercase().contains(keyword) {
-                        // 找到快捷方式，尝试解析目标路径
-                        // 注意：解析 .lnk 需要额外的库，这里简化处理
-                        // 可以考虑使用 mslnk 或 lnk-parser crate
+                        // 找到快捷方式，解析目标路径
                         println!("找到快捷方式: {:?}", path);
+                        if let Some(exe_path) = resolve_shortcut_exe(&path) {
+                            return Some(exe_path);
+                        }
                     }
                 }
             }
@@ -286,74 +311,6 @@ fn search_lnk_files(dir: &PathBuf, keyword: &str) -> Option<String> {
     None
 }
 
-#[cfg(not(target_os = "windows"))]
-pub fn find_in_start_menu() -> Option<String> {
-    None
-}
-
-// ==================== 路径缓存管理 ====================
-
-/// 获取缓存文件路径
-fn get_cache_file_path() -> Option<PathBuf> {
-    if let Some(home_dir) = dirs::home_dir() {
-        let cache_dir = home_dir.join(".bitbrowser");
-
-        // 确保缓存目录存在
-        if !cache_dir.exists() {
-            let _ = fs::create_dir_all(&cache_dir);
-        }
-
-        Some(cache_dir.join(CACHE_FILE_NAME))
-    } else {
-        None
-    }
-}
-
-/// 读取缓存的路径
-fn read_cached_path() -> Option<String> {
-    if let Some(cache_path) = get_cache_file_path() {
-        if cache_path.exists() {
-            if let Ok(mut file) = fs::File::open(&cache_path) {
-                let mut contents = String::new();
-                if file.read_to_string(&mut contents).is_ok() {
-                    let path = contents.trim().to_string();
-
-                    // 验证缓存的路径是否仍然有效
-                    if PathBuf::from(&path).exists() {
-                        println!("✓ 从缓存读取路径: {}", path);
-                        return Some(path);
-                    } else {
-                        println!("⚠ 缓存路径已失效，将重新搜索");
-                        // 删除无效缓存
-                        let _ = fs::remove_file(&cache_path);
-                    }
-                }
-            }
-        }
-    }
-    None
-}
-
-/// 写入路径到缓存
-fn write_cached_path(path: &str) {
-    if let Some(cache_path) = get_cache_file_path() {
-        if let Ok(mut file) = fs::File::create(&cache_path) {
-            let _ = file.write_all(path.as_bytes());
-            println!("✓ 路径已缓存: {}", path);
-        }
-    }
-}
-
-/// 清除路径缓存
-pub fn clear_cached_path() {
-    if let Some(cache_path) = get_cache_file_path() {
-        if cache_path.exists() {
-            let _ = fs::remove_file(&cache_path);
-            println!("✓ 缓存已清除");
-        }
-    }
-}
-
 // ==================== 路径查找 ====================
 
 /// 智能查找 BitBrowser 路径（多策略组合 + 缓存优化）
@@ -362,124 +319,25 @@ pub fn find_bitbrowser_path() -> Option<String> {
 
     // 策略0: 优先检查缓存（最快）
     println!("策略0: 检查缓存...");
-    if let Some(cached_path) = read_cached_path() {
-        return Some(cached_path);
+    if let Some(cache) = bitbrowser_cache::load() {
+        return Some(cache.path);
     }
 
-    // 策略1: 检查进程是否已运行（最快，优先级最高）
-    println!("策略1: 检查正在运行的进程...");
-    if let Some(info) = get_running_bitbrowser_info() {
-        if let Some(path) = info.path {
-            println!("✓ 从运行进程找到: {}", path);
-            write_cached_path(&path);
+    // 策略1+: 按当前系统的查找策略依次执行
+    for (index, strategy) in default_strategies().iter().enumerate() {
+        println!("策略{}: {}...", index + 1, strategy.name());
+        if let Some(path) = strategy.discover() {
+            println!("✓ 通过{}找到: {}", strategy.name(), path);
+            let version = detect_client_version(&path).map(|v| v.version);
+            bitbrowser_cache::store(&path, strategy.id(), version);
             return Some(path);
         }
     }
 
-    // 策略2: 从注册表查找（速度快，准确度高）
-    println!("策略2: 搜索注册表...");
-    if let Some(path) = find_bitbrowser_in_registry() {
-        println!("✓ 从注册表找到: {}", path);
-        write_cached_path(&path);
-        return Some(path);
-    }
-
-    // 策略3: 扫描所有盘符的常见目录
-    println!("策略3: 扫描所有盘符的常见目录...");
-    if let Some(path) = scan_common_directories() {
-        println!("✓ 从目录扫描找到: {}", path);
-        write_cached_path(&path);
-        return Some(path);
-    }
-
-    // 策略4: 搜索开始菜单快捷方式
-    #[cfg(target_os = "windows")]
-    {
-        println!("策略4: 搜索开始菜单...");
-        if let Some(path) = find_in_start_menu() {
-            println!("✓ 从开始菜单找到: {}", path);
-            write_cached_path(&path);
-            return Some(path);
-        }
-    }
-
-    // 策略5: 深度搜索（最后的手段，较慢）
-    println!("策略5: 执行深度搜索...");
-    if let Some(path) = deep_search_bitbrowser() {
-        println!("✓ 从深度搜索找到: {}", path);
-        write_cached_path(&path);
-        return Some(path);
-    }
-
     println!("✗ 未找到 BitBrowser 安装路径");
     None
 }
 
-/// 深度搜索 BitBrowser.exe（遍历所有盘符的根目录，带超时机制）
-fn deep_search_bitbrowser() -> Option<String> {
-    let start_time = Instant::now();
-    let timeout = Duration::from_secs(DEEP_SEARCH_TIMEOUT_SECS);
-    let drives = get_available_drives();
-
-    for drive in drives {
-        // 检查是否超时
-        if start_time.elapsed() >= timeout {
-            println!("⚠ 深度搜索超时（{}秒），停止搜索", DEEP_SEARCH_TIMEOUT_SECS);
-            break;
-        }
-
-        println!("  扫描盘符: {}", drive);
-        let drive_path = PathBuf::from(&drive);
-
-        // 只扫描根目录的直接子目录（避免过深的递归）
-        if let Ok(entries) = fs::read_dir(&drive_path) {
-            for entry in entries.filter_map(|e| e.ok()) {
-                // 每处理几个条目检查一次超时
-                if start_time.elapsed() >= timeout {
-                    println!("⚠ 深度搜索超时（{}秒），停止搜索", DEEP_SEARCH_TIMEOUT_SECS);
-                    return None;
-                }
-
-                let path = entry.path();
-
-                // 跳过系统目录和隐藏目录
-                if let Some(name) = path.file_name() {
-                    let name_str = name.to_string_lossy().to_lowercase();
-                    if name_str.starts_with('$')
-                        || name_str == "windows"
-                        || name_str == "system volume information"
-                        || name_str == "recycler"
-                        || name_str == "$recycle.bin"
-                    {
-                        continue;
-                    }
-                }
-
-                if path.is_dir() {
-                    // 检查该目录下是否有 BitBrowser 可执行文件
-                    if let Some(exe_path) = find_exe_in_directory(&path) {
-                        return Some(exe_path);
-                    }
-
-                    // 再深入一层查找（常见的软件会有子目录）
-                    if let Ok(sub_entries) = fs::read_dir(&path) {
-                        for sub_entry in sub_entries.filter_map(|e| e.ok()).take(50) {
-                            let sub_path = sub_entry.path();
-                            if sub_path.is_dir() {
-                                if let Some(exe_path) = find_exe_in_directory(&sub_path) {
-                                    return Some(exe_path);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    None
-}
-
 /// 启动 BitBrowser
 pub fn start_bitbrowser(path: Option<String>) -> Result<(), String> {
     let exe_path = if let Some(p) = path {
@@ -488,110 +346,187 @@ pub fn start_bitbrowser(path: Option<String>) -> Result<(), String> {
         find_bitbrowser_path().ok_or("无法找到 BitBrowser 安装路径")?
     };
 
-    #[cfg(target_os = "windows")]
-    {
-        Command::new(&exe_path).spawn().map_err(|e| {
-            // 启动失败时清除缓存（可能路径已失效）
-            clear_cached_path();
-            format!("启动 BitBrowser 失败: {}", e)
-        })?;
-    }
+    spawn_bitbrowser(&exe_path, false).map(|_| ())
+}
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        Command::new(&exe_path).spawn().map_err(|e| {
-            // 启动失败时清除缓存（可能路径已失效）
-            clear_cached_path();
-            format!("启动 BitBrowser 失败: {}", e)
-        })?;
+/// 按启动配置启动 BitBrowser 进程并返回子进程句柄
+///
+/// `capture_stderr` 为 true 时通过管道读取标准错误，调用方需要持续读取以免进程阻塞。
+/// 通过 Start-Process 启动时（见 prints_started_pid）同时通过管道读取标准输出，
+/// 第一行是 BitBrowser 的 PID，读到的标准错误来自 PowerShell 而不是 BitBrowser。
+pub fn spawn_bitbrowser(exe_path: &str, capture_stderr: bool) -> Result<Child, String> {
+    let profile = launch_profile();
+    let mut command = build_command(exe_path, &profile);
+    if capture_stderr {
+        command.stderr(Stdio::piped());
+        if prints_started_pid(&profile) {
+            command.stdout(Stdio::piped());
+        }
     }
 
-    Ok(())
+    command.spawn().map_err(|e| {
+        // 启动失败时清除缓存（可能路径已失效）
+        bitbrowser_cache::clear();
+        format!("启动 BitBrowser 失败: {}", e)
+    })
 }
 
-/// 终止 BitBrowser 进程
-pub fn kill_bitbrowser() -> Result<(), String> {
-    let mut system = System::new_all();
-    system.refresh_processes();
+// ==================== API 端口检测 ====================
 
-    let mut killed = false;
+/// 比特浏览器默认 API 端口
+/// 官方文档确认的默认端口是 54345
+pub const DEFAULT_API_PORT: u16 = 54345;
 
-    for (_pid, process) in system.processes() {
-        let name = process.name().to_string();
-        if is_bitbrowser_process(&name) {
-            if process.kill() {
-                killed = true;
-            }
-        }
+/// 未配置 API 地址时依次探测的候选端口（默认端口优先）
+const CANDIDATE_API_PORTS: &[u16] = &[54345, 54346, 54347, 54348, 54349, 54350];
+
+/// 单个端口探测超时时间（毫秒）
+const PORT_PROBE_TIMEOUT_MS: u64 = 800;
+
+/// 用户配置的 API 地址（AppConfig::bitbrowser_api）
+static CONFIGURED_API_URL: RwLock<Option<String>> = RwLock::new(None);
+
+/// 端口探测得到的 API 地址缓存
+static RESOLVED_API_URL: RwLock<Option<String>> = RwLock::new(None);
+
+/// 规范化用户配置的 API 地址（补全协议、去掉末尾斜杠），空值视为未配置
+pub fn normalize_api_url(url: &str) -> Option<String> {
+    let url = url.trim().trim_end_matches('/');
+    if url.is_empty() {
+        return None;
     }
 
-    if killed {
-        Ok(())
+    if url.starts_with("http://") || url.starts_with("https://") {
+        Some(url.to_string())
     } else {
-        Err("未找到 BitBrowser 进程".to_string())
+        Some(format!("http://{}", url))
     }
 }
 
-// ==================== API 端口检测 ====================
+/// 解析 API 地址（没有协议时按 http 处理）
+fn parse_api_url(url: &str) -> Option<reqwest::Url> {
+    let normalized = normalize_api_url(url)?;
+    reqwest::Url::parse(&normalized).ok()
+}
 
-/// 比特浏览器默认 API 端口
-/// 官方文档确认的默认端口是 54345
-const DEFAULT_API_PORT: u16 = 54345;
+/// 从 API 地址中解析端口（未指定时按协议取默认端口）
+pub fn api_port_from_url(url: &str) -> Option<u16> {
+    parse_api_url(url)?.port_or_known_default()
+}
 
+/// 判断 API 地址是否指向本机
+pub fn is_local_api_url(url: &str) -> bool {
+    parse_api_url(url).map_or(false, |url| {
+        matches!(url.host_str(), Some("127.0.0.1" | "localhost" | "0.0.0.0" | "[::1]"))
+    })
+}
 
-/// 检查指定端口是否被占用，并返回占用该端口的进程 PID
-#[cfg(target_os = "windows")]
-pub fn find_process_using_port(port: u16) -> Option<u32> {
-    use std::process::Command;
-
-    // 使用 netstat 查找占用指定端口的进程
-    let output = Command::new("netstat")
-        .args(&["-ano"])
-        .output()
-        .ok()?;
-
-    let output_str = String::from_utf8_lossy(&output.stdout);
-
-    // 查找包含指定端口的行
-    for line in output_str.lines() {
-        if line.contains(&format!(":{}", port)) && line.contains("LISTENING") {
-            // 提取 PID（最后一列）
-            if let Some(pid_str) = line.split_whitespace().last() {
-                if let Ok(pid) = pid_str.parse::<u32>() {
-                    return Some(pid);
-                }
-            }
-        }
-    }
+/// 设置用户配置的 API 地址（None 或空字符串表示自动探测端口）
+pub fn set_configured_api_url(url: Option<String>) {
+    let normalized = url.as_deref().and_then(normalize_api_url);
+    println!(
+        "✓ BitBrowser API 地址: {}",
+        normalized.as_deref().unwrap_or("自动探测")
+    );
+    *CONFIGURED_API_URL.write().unwrap() = normalized;
+    invalidate_resolved_api_url();
+}
 
-    None
+/// 清除端口探测缓存（连接失败时调用，下次重新探测）
+pub fn invalidate_resolved_api_url() {
+    *RESOLVED_API_URL.write().unwrap() = None;
 }
 
-#[cfg(not(target_os = "windows"))]
-pub fn find_process_using_port(port: u16) -> Option<u32> {
-    use std::process::Command;
-
-    // 使用 lsof 查找占用指定端口的进程
-    let output = Command::new("lsof")
-        .args(&["-i", &format!(":{}", port), "-t"])
-        .output()
-        .ok()?;
-
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    if let Some(first_line) = output_str.lines().next() {
-        if let Ok(pid) = first_line.trim().parse::<u32>() {
-            return Some(pid);
-        }
+/// 探测单个端口是否为 BitBrowser API
+async fn probe_api_port(port: u16) -> bool {
+    let client = crate::bitbrowser_client::shared_http_client();
+    let request = client
+        .post(format!("http://127.0.0.1:{}/browser/list", port))
+        .json(&serde_json::json!({ "page": 0, "pageSize": 1 }))
+        .send();
+
+    let timeout = Duration::from_millis(PORT_PROBE_TIMEOUT_MS);
+    let response = match tokio::time::timeout(timeout, request).await {
+        Ok(Ok(response)) => response,
+        _ => return false,
+    };
+
+    // 开启鉴权的 BitBrowser 未携带正确 Token 时返回 401
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return true;
     }
 
-    None
+    // 只接受 BitBrowser 格式的响应，避免误判占用候选端口的其他服务
+    match response.json::<serde_json::Value>().await {
+        Ok(json) => json.get("success").is_some(),
+        Err(_) => false,
+    }
 }
 
-
-
 /// 获取比特浏览器 API 基础 URL
-/// 比特浏览器的 API 端口固定为 54345，直接返回即可（参考官方 demo）
+///
+/// 优先使用配置的地址；未配置时并发探测候选端口，
+/// 全部失败则返回默认端口（交给 detector 诊断断连原因）
 pub async fn get_api_base_url() -> Result<String, String> {
-    Ok(format!("http://127.0.0.1:{}", DEFAULT_API_PORT))
+    if let Some(url) = CONFIGURED_API_URL.read().unwrap().clone() {
+        return Ok(url);
+    }
+
+    if let Some(url) = RESOLVED_API_URL.read().unwrap().clone() {
+        return Ok(url);
+    }
+
+    let results = futures::future::join_all(
+        CANDIDATE_API_PORTS.iter().map(|port| probe_api_port(*port)),
+    )
+    .await;
+
+    let found = CANDIDATE_API_PORTS
+        .iter()
+        .zip(results)
+        .find(|(_, ok)| *ok)
+        .map(|(port, _)| *port);
+
+    match found {
+        Some(port) => {
+            let url = format!("http://127.0.0.1:{}", port);
+            if port != DEFAULT_API_PORT {
+                println!("✓ 探测到 BitBrowser API 端口: {}", port);
+            }
+            *RESOLVED_API_URL.write().unwrap() = Some(url.clone());
+            Ok(url)
+        }
+        None => Ok(format!("http://127.0.0.1:{}", DEFAULT_API_PORT)),
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_api_url() {
+        assert_eq!(normalize_api_url("  "), None);
+        assert_eq!(
+            normalize_api_url("192.168.1.20:54345/"),
+            Some("http://192.168.1.20:54345".to_string())
+        );
+        assert_eq!(
+            normalize_api_url("https://bb.lan"),
+            Some("https://bb.lan".to_string())
+        );
+    }
+
+    #[test]
+    fn test_api_port_and_locality() {
+        assert_eq!(api_port_from_url("http://127.0.0.1:54346"), Some(54346));
+        assert_eq!(api_port_from_url("http://bb.lan/api"), Some(80));
+        assert_eq!(api_port_from_url("https://bb.lan"), Some(443));
+        assert_eq!(api_port_from_url("http://[::1]"), Some(80));
+        assert_eq!(api_port_from_url("http://[::1]:54346/api"), Some(54346));
+        assert_eq!(api_port_from_url("127.0.0.1:54347"), Some(54347));
+        assert!(is_local_api_url("http://localhost:54345"));
+        assert!(is_local_api_url("http://[::1]"));
+        assert!(!is_local_api_url("http://192.168.1.20:54345"));
+    }
+}
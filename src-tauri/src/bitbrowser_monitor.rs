@@ -3,19 +3,23 @@
  * 负责后台监控 BitBrowser 连接状态
  *
  * 功能：
- * - 定期检测连接状态
- * - 向前端推送状态变化事件
+ * - 自适应检测间隔：初始化中快速检测，未运行时指数退避
+ * - 只在状态变化时向前端推送事件（可选心跳）
+ * - 支持手动触发立即检测
+ * - 支持暂停、恢复和重新配置检测间隔
+ * - 记录状态变化历史
+ * - 可选的自动恢复（见 bitbrowser_recovery）
  */
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tauri::Manager;
+use tokio::sync::{watch, Notify};
 
 use crate::bitbrowser_detector::{check_status, ConnectionStatus, DisconnectReason};
-
-/// 监控间隔（秒）
-const MONITOR_INTERVAL_SECS: u64 = 10;
+use crate::bitbrowser_history::{ConnectionHistory, ConnectionStats, StatusTransition};
+use crate::bitbrowser_recovery::{self, RecoveryPolicy, RecoveryTracker};
 
 /// 前端事件名称
 const EVENT_NAME: &str = "bitbrowser-status";
@@ -28,76 +32,472 @@ pub struct StatusEvent {
     pub timestamp: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<DisconnectReason>,
+    /// BitBrowser 客户端版本（已连接时）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl StatusEvent {
+    fn from_status(status: ConnectionStatus) -> Self {
+        match status {
+            ConnectionStatus::Connected { message, version } => StatusEvent {
+                connected: true,
+                message,
+                timestamp: get_timestamp(),
+                reason: None,
+                version: version.map(|v| v.version),
+            },
+            ConnectionStatus::Disconnected { message, reason } => StatusEvent {
+                connected: false,
+                message,
+                timestamp: get_timestamp(),
+                reason: Some(reason),
+                version: None,
+            },
+        }
+    }
+
+    /// 是否与另一个事件处于同一状态（ApiError 的错误文本不参与比较）
+    fn same_state(&self, other: &StatusEvent) -> bool {
+        if self.connected != other.connected {
+            return false;
+        }
+
+        match (&self.reason, &other.reason) {
+            (None, None) => true,
+            (Some(DisconnectReason::ApiError { .. }), Some(DisconnectReason::ApiError { .. })) => true,
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// 监控配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorConfig {
+    /// 常规检测间隔（秒）
+    pub interval_secs: u64,
+    /// BitBrowser 初始化中时的检测间隔（秒）
+    pub initializing_interval_secs: u64,
+    /// BitBrowser 未运行时退避的最大间隔（秒）
+    pub max_backoff_secs: u64,
+    /// 心跳间隔（秒）：状态未变化时也按此间隔推送，None 表示只在状态变化时推送
+    pub heartbeat_secs: Option<u64>,
+    /// 自动恢复策略（默认关闭，保存在 settings.json 的 bitbrowser_recovery 中）
+    #[serde(default)]
+    pub recovery: RecoveryPolicy,
+}
+
+/// 监控配置的部分更新（未提供的字段保持当前值）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MonitorConfigUpdate {
+    pub interval_secs: Option<u64>,
+    pub initializing_interval_secs: Option<u64>,
+    pub max_backoff_secs: Option<u64>,
+    /// 心跳间隔（秒），0 表示关闭心跳
+    pub heartbeat_secs: Option<u64>,
+}
+
+impl MonitorConfig {
+    /// 合并部分更新
+    pub fn merge(&self, update: MonitorConfigUpdate) -> MonitorConfig {
+        MonitorConfig {
+            interval_secs: update.interval_secs.unwrap_or(self.interval_secs),
+            initializing_interval_secs: update
+                .initializing_interval_secs
+                .unwrap_or(self.initializing_interval_secs),
+            max_backoff_secs: update.max_backoff_secs.unwrap_or(self.max_backoff_secs),
+            heartbeat_secs: match update.heartbeat_secs {
+                Some(0) => None,
+                Some(secs) => Some(secs),
+                None => self.heartbeat_secs,
+            },
+            recovery: self.recovery.clone(),
+        }
+    }
+
+    /// 校验配置
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_secs == 0 || self.initializing_interval_secs == 0 {
+            return Err("检测间隔必须大于 0 秒".to_string());
+        }
+        self.recovery.validate()
+    }
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        MonitorConfig {
+            interval_secs: 10,
+            initializing_interval_secs: 2,
+            max_backoff_secs: 60,
+            heartbeat_secs: None,
+            recovery: RecoveryPolicy::default(),
+        }
+    }
+}
+
+/// 监控运行时状态（保存在 AppState 中）
+pub struct MonitorState {
+    config: Mutex<MonitorConfig>,
+    inner: Mutex<MonitorInner>,
+    /// 状态变化历史
+    history: Mutex<ConnectionHistory>,
+    /// 自动恢复决策状态
+    recovery: Mutex<RecoveryTracker>,
+    /// 手动检测唤醒信号
+    recheck: Notify,
+    /// 下一次检测无论状态是否变化都推送
+    force_emit: AtomicBool,
+}
+
+#[derive(Default)]
+struct MonitorInner {
+    /// 上次推送的事件
+    last_event: Option<StatusEvent>,
+    /// 上次检测到的状态（用于判断状态变化）
+    last_state: Option<StatusEvent>,
+    /// 上次推送的时间
+    last_emit_at: Option<Instant>,
+    /// 连续检测到未运行的次数（用于退避）
+    not_running_streak: u32,
+}
+
+impl MonitorState {
+    pub fn new(config: MonitorConfig) -> Self {
+        MonitorState {
+            config: Mutex::new(config),
+            inner: Mutex::new(MonitorInner::default()),
+            history: Mutex::new(ConnectionHistory::default()),
+            recovery: Mutex::new(RecoveryTracker::default()),
+            recheck: Notify::new(),
+            force_emit: AtomicBool::new(false),
+        }
+    }
+
+    /// 当前监控配置
+    pub fn config(&self) -> MonitorConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    /// 更新监控配置，并唤醒监控循环按新间隔重新计时
+    pub fn set_config(&self, config: MonitorConfig) {
+        *self.config.lock().unwrap() = config;
+        self.recheck.notify_one();
+    }
+
+    /// 更新自动恢复策略（由配置订阅调用）
+    pub fn set_recovery_policy(&self, policy: RecoveryPolicy) {
+        if policy.enabled {
+            println!(
+                "✓ 自动恢复已开启：未运行 {} 秒后启动，最多尝试 {} 次",
+                policy.not_running_grace_secs, policy.max_attempts
+            );
+        }
+        self.config.lock().unwrap().recovery = policy;
+        self.recheck.notify_one();
+    }
+
+    /// 把部分更新合并到当前配置，返回合并后的配置（自动恢复策略见 set_recovery_policy）
+    pub fn update_config(&self, update: MonitorConfigUpdate) -> Result<MonitorConfig, String> {
+        let mut config = self.config.lock().unwrap();
+        let merged = config.merge(update);
+        merged.validate()?;
+        *config = merged.clone();
+        drop(config);
+
+        self.recheck.notify_one();
+        Ok(merged)
+    }
+
+    /// 请求立即检测（检测结果会强制推送到前端）
+    pub fn request_recheck(&self) {
+        self.force_emit.store(true, Ordering::Relaxed);
+        self.recheck.notify_one();
+    }
+
+    /// 上次推送的状态事件
+    pub fn last_event(&self) -> Option<StatusEvent> {
+        self.inner.lock().unwrap().last_event.clone()
+    }
+
+    /// 状态变化历史（从旧到新）
+    pub fn history(&self) -> Vec<StatusTransition> {
+        self.history.lock().unwrap().entries()
+    }
+
+    /// 连接统计（在线时长、断连次数、是否抖动）
+    pub fn stats(&self) -> ConnectionStats {
+        self.history.lock().unwrap().stats(get_timestamp())
+    }
+
+    /// 记录一次检测结果，返回是否需要推送
+    fn record(&self, event: &StatusEvent, now: Instant) -> bool {
+        let heartbeat = self
+            .config
+            .lock()
+            .unwrap()
+            .heartbeat_secs
+            .map(Duration::from_secs);
+        let force = self.force_emit.swap(false, Ordering::Relaxed);
+        let mut inner = self.inner.lock().unwrap();
+
+        if matches!(event.reason, Some(DisconnectReason::NotRunning)) {
+            inner.not_running_streak += 1;
+        } else {
+            inner.not_running_streak = 0;
+        }
+
+        // 状态变化写入历史
+        let changed = inner
+            .last_state
+            .as_ref()
+            .map_or(true, |last| !last.same_state(event));
+        if changed {
+            self.history.lock().unwrap().push(
+                event.connected,
+                event.message.clone(),
+                event.reason.clone(),
+                event.timestamp,
+            );
+        }
+        inner.last_state = Some(event.clone());
+
+        let emit = force
+            || should_emit(
+                inner.last_event.as_ref(),
+                event,
+                inner.last_emit_at.map(|t| now.duration_since(t)),
+                heartbeat,
+            );
+
+        if emit {
+            inner.last_event = Some(event.clone());
+            inner.last_emit_at = Some(now);
+        }
+
+        emit
+    }
+
+    /// 自动恢复已尝试的次数
+    pub fn recovery_attempts(&self) -> u32 {
+        self.recovery.lock().unwrap().attempts()
+    }
+
+    /// 根据最近一次检测结果计算下一次检测前的等待时间
+    fn next_delay(&self, event: &StatusEvent) -> Duration {
+        let config = self.config.lock().unwrap().clone();
+        let streak = self.inner.lock().unwrap().not_running_streak;
+        compute_delay(&config, event, streak)
+    }
+}
+
+impl Default for MonitorState {
+    fn default() -> Self {
+        Self::new(MonitorConfig::default())
+    }
+}
+
+/// 判断是否需要推送事件
+///
+/// - 首次检测或状态变化时推送
+/// - 开启心跳时，距上次推送超过心跳间隔也推送
+fn should_emit(
+    last: Option<&StatusEvent>,
+    current: &StatusEvent,
+    since_last_emit: Option<Duration>,
+    heartbeat: Option<Duration>,
+) -> bool {
+    let changed = match last {
+        Some(last) => !last.same_state(current),
+        None => true,
+    };
+
+    let heartbeat_due = match (heartbeat, since_last_emit) {
+        (Some(heartbeat), Some(elapsed)) => elapsed >= heartbeat,
+        _ => false,
+    };
+
+    changed || heartbeat_due
 }
 
-/// 启动后台监控任务
+/// 计算检测间隔
+///
+/// - 初始化中：快速检测
+/// - 未运行：从常规间隔开始指数退避，不超过最大间隔
+/// - 其他：常规间隔
+fn compute_delay(config: &MonitorConfig, event: &StatusEvent, not_running_streak: u32) -> Duration {
+    let secs = match &event.reason {
+        Some(DisconnectReason::Initializing) => config.initializing_interval_secs,
+        Some(DisconnectReason::NotRunning) => {
+            let exponent = not_running_streak.saturating_sub(1).min(16);
+            config
+                .interval_secs
+                .saturating_mul(1u64 << exponent)
+                .min(config.max_backoff_secs.max(config.interval_secs))
+        }
+        _ => config.interval_secs,
+    };
+
+    Duration::from_secs(secs.max(1))
+}
+
+/// 后台监控任务句柄
+struct MonitorHandle {
+    /// 取消信号（发送 true 通知监控循环退出）
+    cancel: watch::Sender<bool>,
+    join: tauri::async_runtime::JoinHandle<()>,
+    /// 任务是否已退出（正常退出或 panic）
+    finished: Arc<AtomicBool>,
+}
+
+/// 任务退出时（包括 panic 展开时）标记为已退出
+struct FinishGuard(Arc<AtomicBool>);
+
+impl Drop for FinishGuard {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 后台监控任务控制器（保存在 AppState 中）
+pub struct MonitorController {
+    state: Arc<MonitorState>,
+    handle: Mutex<Option<MonitorHandle>>,
+}
+
+impl MonitorController {
+    pub fn new(state: Arc<MonitorState>) -> Self {
+        MonitorController {
+            state,
+            handle: Mutex::new(None),
+        }
+    }
+
+    /// 监控运行时状态
+    pub fn state(&self) -> &Arc<MonitorState> {
+        &self.state
+    }
+
+    /// 监控任务是否正在运行
+    pub fn is_running(&self) -> bool {
+        self.handle
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(false, |handle| !handle.finished.load(Ordering::Relaxed))
+    }
+
+    /// 启动监控任务，已在运行时返回 false
+    ///
+    /// 是否在运行以任务句柄为准：正在停止的任务句柄已被 stop 取走，
+    /// 异常退出的任务会被标记为已退出，都不会阻止重新启动
+    pub fn start(&self, app_handle: tauri::AppHandle) -> bool {
+        let mut handle = self.handle.lock().unwrap();
+        if handle
+            .as_ref()
+            .map_or(false, |handle| !handle.finished.load(Ordering::Relaxed))
+        {
+            return false;
+        }
+
+        let (cancel, cancelled) = watch::channel(false);
+        let state = self.state.clone();
+        let finished = Arc::new(AtomicBool::new(false));
+        let guard = FinishGuard(finished.clone());
+        let join = tauri::async_runtime::spawn(async move {
+            let _guard = guard;
+            run_monitor(app_handle, state, cancelled).await;
+        });
+
+        *handle = Some(MonitorHandle { cancel, join, finished });
+        true
+    }
+
+    /// 停止监控任务并等待其退出，未运行时返回 false
+    pub async fn stop(&self) -> bool {
+        let handle = self.handle.lock().unwrap().take();
+        match handle {
+            Some(handle) => {
+                let _ = handle.cancel.send(true);
+                let _ = handle.join.await;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// 后台监控循环
 ///
 /// # 参数
 /// - `app_handle`: Tauri 应用句柄
-/// - `running`: 控制监控任务运行的原子布尔值
-pub async fn start_monitor(app_handle: tauri::AppHandle, running: Arc<AtomicBool>) {
+/// - `state`: 监控运行时状态
+/// - `cancelled`: 取消信号，收到 true 时退出
+async fn run_monitor(
+    app_handle: tauri::AppHandle,
+    state: Arc<MonitorState>,
+    mut cancelled: watch::Receiver<bool>,
+) {
     println!("✓ BitBrowser 后台监控任务已启动");
 
-    // 立即执行第一次检测（代理问题已修复，无需等待）
-    check_and_emit(&app_handle).await;
+    while !*cancelled.borrow() {
+        // 执行检测并推送事件
+        let event = check_and_emit(&app_handle, &state).await;
 
-    // 定期检测循环
-    while running.load(Ordering::Relaxed) {
-        // 等待指定间隔
-        tokio::time::sleep(Duration::from_secs(MONITOR_INTERVAL_SECS)).await;
+        // 按策略执行自动恢复
+        recover_if_needed(&app_handle, &state, &event).await;
 
-        // 如果已停止，退出循环
-        if !running.load(Ordering::Relaxed) {
-            break;
+        // 等待下一次检测，或被手动检测请求、取消信号唤醒
+        let delay = state.next_delay(&event);
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = state.recheck.notified() => {}
+            _ = cancelled.changed() => {}
         }
-
-        // 执行检测并推送事件
-        check_and_emit(&app_handle).await;
     }
 
     println!("✓ BitBrowser 后台监控任务已停止");
 }
 
-/// 上次的连接状态（用于检测状态变化）
-static LAST_CONNECTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+/// 执行检测，状态变化（或心跳、手动检测）时向前端推送事件
+async fn check_and_emit(app_handle: &tauri::AppHandle, state: &MonitorState) -> StatusEvent {
+    let event = StatusEvent::from_status(check_status().await);
+    let previous = state.last_event();
 
-/// 执行检测并向前端推送事件
-async fn check_and_emit(app_handle: &tauri::AppHandle) {
-    // 检测连接状态
-    let status = check_status().await;
+    if state.record(&event, Instant::now()) {
+        if let Err(e) = app_handle.emit_all(EVENT_NAME, &event) {
+            eprintln!("⚠ 推送状态事件失败: {}", e);
+        }
 
-    // 构造事件 payload
-    let event = match status {
-        ConnectionStatus::Connected { message } => StatusEvent {
-            connected: true,
-            message,
-            timestamp: get_timestamp(),
-            reason: None,
-        },
-        ConnectionStatus::Disconnected { message, reason } => StatusEvent {
-            connected: false,
-            message,
-            timestamp: get_timestamp(),
-            reason: Some(reason),
-        },
+        if previous.as_ref().map_or(true, |p| !p.same_state(&event)) {
+            println!(
+                "✓ 连接状态变化: {} -> {}",
+                previous.as_ref().map_or("未知", |p| p.message.as_str()),
+                event.message
+            );
+        }
+    }
+
+    event
+}
+
+/// 按自动恢复策略处理最近一次检测结果
+async fn recover_if_needed(app_handle: &tauri::AppHandle, state: &MonitorState, event: &StatusEvent) {
+    let policy = state.config.lock().unwrap().recovery.clone();
+    let (action, attempt) = {
+        let mut tracker = state.recovery.lock().unwrap();
+        let action = tracker.evaluate(&policy, event.reason.as_ref(), Instant::now());
+        (action, tracker.attempts())
     };
 
-    // 检查状态是否变化
-    let last_connected = LAST_CONNECTED.load(std::sync::atomic::Ordering::Relaxed);
-    let status_changed = last_connected != event.connected;
-
-    // 更新状态
-    LAST_CONNECTED.store(event.connected, std::sync::atomic::Ordering::Relaxed);
-
-    // 推送到前端
-    if let Err(e) = app_handle.emit_all(EVENT_NAME, &event) {
-        eprintln!("⚠ 推送状态事件失败: {}", e);
-    } else if status_changed {
-        // 只在状态变化时输出日志
-        println!("✓ 连接状态变化: {} -> {}",
-            if last_connected { "已连接" } else { "未连接" },
-            if event.connected { "已连接" } else { "未连接" }
-        );
+    if let Some(action) = action {
+        bitbrowser_recovery::execute(app_handle, action, attempt, policy.max_attempts).await;
+
+        // 启动后尽快检测，不再沿用未运行时的退避间隔
+        state.inner.lock().unwrap().not_running_streak = 0;
     }
 }
 
@@ -113,10 +513,141 @@ fn get_timestamp() -> u64 {
 mod tests {
     use super::*;
 
+    fn event(reason: Option<DisconnectReason>) -> StatusEvent {
+        StatusEvent {
+            connected: reason.is_none(),
+            message: String::new(),
+            timestamp: get_timestamp(),
+            reason,
+            version: None,
+        }
+    }
+
     #[test]
     fn test_get_timestamp() {
         let ts = get_timestamp();
         assert!(ts > 0);
         println!("当前时间戳: {}", ts);
     }
+
+    #[test]
+    fn test_emit_only_on_transition() {
+        let state = MonitorState::default();
+        let now = Instant::now();
+
+        assert!(state.record(&event(None), now));
+        assert!(!state.record(&event(None), now + Duration::from_secs(10)));
+        assert!(state.record(&event(Some(DisconnectReason::NotRunning)), now + Duration::from_secs(20)));
+        assert!(state.record(&event(Some(DisconnectReason::Initializing)), now + Duration::from_secs(30)));
+
+        // ApiError 的错误文本变化不算状态变化
+        let api_error = |e: &str| event(Some(DisconnectReason::ApiError { error: e.to_string() }));
+        assert!(state.record(&api_error("a"), now + Duration::from_secs(40)));
+        assert!(!state.record(&api_error("b"), now + Duration::from_secs(50)));
+    }
+
+    #[test]
+    fn test_history_records_transitions_only() {
+        let state = MonitorState::new(MonitorConfig {
+            heartbeat_secs: Some(1),
+            ..Default::default()
+        });
+        let now = Instant::now();
+
+        state.record(&event(Some(DisconnectReason::NotRunning)), now);
+        state.record(&event(Some(DisconnectReason::NotRunning)), now + Duration::from_secs(10));
+        state.record(&event(None), now + Duration::from_secs(20));
+        state.record(&event(None), now + Duration::from_secs(30));
+
+        let history = state.history();
+        assert_eq!(history.len(), 2);
+        assert!(!history[0].connected);
+        assert!(history[1].connected);
+        assert_eq!(state.stats().outage_count, 1);
+    }
+
+    #[test]
+    fn test_heartbeat_and_forced_emit() {
+        let state = MonitorState::new(MonitorConfig {
+            heartbeat_secs: Some(30),
+            ..Default::default()
+        });
+        let now = Instant::now();
+
+        assert!(state.record(&event(None), now));
+        assert!(!state.record(&event(None), now + Duration::from_secs(10)));
+        assert!(state.record(&event(None), now + Duration::from_secs(30)));
+
+        state.request_recheck();
+        assert!(state.record(&event(None), now + Duration::from_secs(31)));
+    }
+
+    #[test]
+    fn test_partial_config_update_keeps_other_fields() {
+        let state = MonitorState::default();
+        state.set_config(MonitorConfig {
+            heartbeat_secs: Some(30),
+            recovery: RecoveryPolicy {
+                enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let config = state
+            .update_config(MonitorConfigUpdate {
+                interval_secs: Some(5),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(config.interval_secs, 5);
+        assert_eq!(config.heartbeat_secs, Some(30));
+        assert!(config.recovery.enabled);
+
+        let config = state
+            .update_config(MonitorConfigUpdate {
+                heartbeat_secs: Some(0),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(config.heartbeat_secs, None);
+
+        // 无效的更新不生效
+        let invalid = MonitorConfigUpdate {
+            interval_secs: Some(0),
+            ..Default::default()
+        };
+        assert!(state.update_config(invalid).is_err());
+        assert_eq!(state.config().interval_secs, 5);
+    }
+
+    #[test]
+    fn test_finish_guard_marks_panicked_task() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let guard = FinishGuard(finished.clone());
+        let result = std::thread::spawn(move || {
+            let _guard = guard;
+            panic!("monitor task panicked");
+        })
+        .join();
+
+        assert!(result.is_err());
+        assert!(finished.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_compute_delay() {
+        let config = MonitorConfig::default();
+        let not_running = event(Some(DisconnectReason::NotRunning));
+
+        assert_eq!(compute_delay(&config, &event(None), 0), Duration::from_secs(10));
+        assert_eq!(
+            compute_delay(&config, &event(Some(DisconnectReason::Initializing)), 0),
+            Duration::from_secs(2)
+        );
+        assert_eq!(compute_delay(&config, &not_running, 1), Duration::from_secs(10));
+        assert_eq!(compute_delay(&config, &not_running, 2), Duration::from_secs(20));
+        assert_eq!(compute_delay(&config, &not_running, 3), Duration::from_secs(40));
+        assert_eq!(compute_delay(&config, &not_running, 10), Duration::from_secs(60));
+    }
 }
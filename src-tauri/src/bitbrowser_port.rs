@@ -0,0 +1,350 @@
+/**
+ * BitBrowser Port
+ * 处理 BitBrowser API 端口被其他进程占用的情况
+ *
+ * 流程：
+ * 1. inspect：返回占用端口进程的详细信息（路径、命令行、启动时间、父进程）
+ *    和一次性确认令牌；系统关键进程不发放令牌
+ * 2. resolve：前端确认后凭令牌结束进程（进程已变化时拒绝），
+ *    等待端口释放并验证端口可以重新绑定
+ */
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, System};
+
+use crate::bitbrowser_manager::is_bitbrowser_process;
+use crate::port_owner::find_process_using_port;
+
+/// 确认令牌有效期（秒）
+const TOKEN_TTL_SECS: u64 = 120;
+
+/// 结束进程后等待端口释放的最长时间（毫秒）
+const PORT_RELEASE_TIMEOUT_MS: u64 = 10_000;
+
+/// 等待端口释放时的轮询间隔（毫秒）
+const PORT_POLL_INTERVAL_MS: u64 = 250;
+
+/// 不允许结束的系统进程（小写）
+const CRITICAL_PROCESS_NAMES: &[&str] = &[
+    // Windows
+    "system",
+    "idle",
+    "smss.exe",
+    "csrss.exe",
+    "wininit.exe",
+    "winlogon.exe",
+    "services.exe",
+    "lsass.exe",
+    "svchost.exe",
+    "dwm.exe",
+    "explorer.exe",
+    // Linux / macOS
+    "init",
+    "systemd",
+    "launchd",
+    "kernel_task",
+    "sshd",
+];
+
+/// 待确认的结束请求（令牌 -> 请求）
+static PENDING: OnceLock<Mutex<HashMap<String, PendingKill>>> = OnceLock::new();
+
+/// 受保护进程的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtectedKind {
+    /// 系统进程
+    System,
+    /// 当前应用自身
+    CurrentApp,
+    /// BitBrowser 本身（不属于端口冲突）
+    BitBrowser,
+}
+
+/// 占用端口的进程信息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortOwner {
+    pub port: u16,
+    pub pid: u32,
+    pub name: String,
+    pub exe_path: Option<String>,
+    pub cmdline: Vec<String>,
+    /// 进程启动时间（Unix 时间戳，秒）
+    pub start_time: u64,
+    pub parent_pid: Option<u32>,
+    pub parent_name: Option<String>,
+    /// 不允许结束的原因，None 表示可以结束
+    pub protected_reason: Option<String>,
+    /// 受保护进程的类型，None 表示可以结束
+    pub protected_kind: Option<ProtectedKind>,
+}
+
+/// 检查结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortInspection {
+    pub owner: PortOwner,
+    /// 确认令牌，受保护的进程没有令牌
+    pub confirm_token: Option<String>,
+    /// 令牌有效期（秒）
+    pub expires_in_secs: u64,
+}
+
+/// 处理结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortResolution {
+    pub port: u16,
+    pub killed_pid: u32,
+    /// 端口是否已释放
+    pub port_released: bool,
+    /// 端口是否可以重新绑定
+    pub bindable: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+struct PendingKill {
+    port: u16,
+    pid: u32,
+    start_time: u64,
+    expires_at: Instant,
+}
+
+fn pending() -> &'static Mutex<HashMap<String, PendingKill>> {
+    PENDING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 判断进程是否受保护，返回受保护的类型和不允许结束的原因
+fn protection(pid: u32, name: &str, exe_path: Option<&Path>) -> Option<(ProtectedKind, String)> {
+    if pid <= 4 {
+        return Some((ProtectedKind::System, "系统核心进程".to_string()));
+    }
+
+    if pid == std::process::id() {
+        return Some((ProtectedKind::CurrentApp, "当前应用自身".to_string()));
+    }
+
+    if is_bitbrowser_process(name) {
+        return Some((
+            ProtectedKind::BitBrowser,
+            "该进程是 BitBrowser 本身，不属于端口冲突".to_string(),
+        ));
+    }
+
+    let lower = name.to_lowercase();
+    if CRITICAL_PROCESS_NAMES.contains(&lower.as_str()) {
+        return Some((ProtectedKind::System, format!("{} 是系统关键进程", name)));
+    }
+
+    if let Some(path) = exe_path {
+        let path = path.to_string_lossy().to_lowercase().replace('/', "\\");
+        if path.starts_with("c:\\windows\\system32\\") || path.starts_with("c:\\windows\\syswow64\\") {
+            return Some((ProtectedKind::System, format!("{} 位于系统目录", name)));
+        }
+    }
+
+    None
+}
+
+/// 读取占用端口的进程详细信息
+fn describe_owner(system: &System, port: u16, pid: u32) -> Option<PortOwner> {
+    let process = system.process(Pid::from_u32(pid))?;
+    let name = process.name().to_string();
+    let parent = process.parent();
+    let (protected_kind, protected_reason) = protection(pid, &name, process.exe()).unzip();
+
+    Some(PortOwner {
+        port,
+        pid,
+        exe_path: process.exe().map(|p| p.to_string_lossy().to_string()),
+        cmdline: process.cmd().to_vec(),
+        start_time: process.start_time(),
+        parent_pid: parent.map(|p| p.as_u32()),
+        parent_name: parent
+            .and_then(|p| system.process(p))
+            .map(|p| p.name().to_string()),
+        protected_reason,
+        protected_kind,
+        name,
+    })
+}
+
+/// 读取进程列表（只刷新进程信息）
+fn process_list() -> System {
+    let mut system = System::new();
+    system.refresh_processes();
+    system
+}
+
+/// 检查占用端口的进程，可结束时发放确认令牌
+///
+/// # 返回
+/// - `Ok(None)`: 端口未被占用
+pub async fn inspect(port: u16) -> Result<Option<PortInspection>, String> {
+    // 查找端口和读取进程列表都是阻塞操作
+    tokio::task::spawn_blocking(move || inspect_blocking(port))
+        .await
+        .map_err(|e| format!("检查端口占用失败: {}", e))?
+}
+
+fn inspect_blocking(port: u16) -> Result<Option<PortInspection>, String> {
+    let pid = match find_process_using_port(port) {
+        Some(pid) => pid,
+        None => return Ok(None),
+    };
+
+    let system = process_list();
+    let owner = describe_owner(&system, port, pid)
+        .ok_or_else(|| format!("无法读取进程 PID: {} 的信息", pid))?;
+
+    let confirm_token = if owner.protected_reason.is_none() {
+        let token = uuid::Uuid::new_v4().to_string();
+        let now = Instant::now();
+        let mut pending = pending().lock().unwrap();
+        pending.retain(|_, p| p.expires_at > now);
+        pending.insert(
+            token.clone(),
+            PendingKill {
+                port,
+                pid,
+                start_time: owner.start_time,
+                expires_at: now + Duration::from_secs(TOKEN_TTL_SECS),
+            },
+        );
+        Some(token)
+    } else {
+        None
+    };
+
+    Ok(Some(PortInspection {
+        owner,
+        confirm_token,
+        expires_in_secs: TOKEN_TTL_SECS,
+    }))
+}
+
+/// 取出并校验确认令牌（令牌只能使用一次）
+fn take_token(token: &str, now: Instant) -> Result<PendingKill, String> {
+    let request = pending()
+        .lock()
+        .unwrap()
+        .remove(token)
+        .ok_or_else(|| "确认令牌无效或已使用，请重新检查端口".to_string())?;
+
+    if request.expires_at <= now {
+        return Err("确认令牌已过期，请重新检查端口".to_string());
+    }
+
+    Ok(request)
+}
+
+/// 确认占用端口的仍是同一个可结束的进程后结束它
+fn kill_owner(request: &PendingKill) -> Result<(), String> {
+    // 确认占用端口的仍是同一个进程（防止 PID 被复用）
+    let system = process_list();
+    let current_pid = find_process_using_port(request.port);
+    let process = system
+        .process(Pid::from_u32(request.pid))
+        .filter(|p| p.start_time() == request.start_time && current_pid == Some(request.pid))
+        .ok_or_else(|| format!("端口 {} 的占用进程已变化，请重新检查", request.port))?;
+
+    // 发放令牌后进程信息可能已变化，结束前重新检查
+    if let Some((_, reason)) = protection(request.pid, process.name(), process.exe()) {
+        return Err(format!("无法结束进程 {}：{}", process.name(), reason));
+    }
+
+    println!(
+        "结束占用端口 {} 的进程 {} (PID: {})",
+        request.port,
+        process.name(),
+        request.pid
+    );
+    if !process.kill() {
+        return Err(format!("无法结束进程 PID: {}", request.pid));
+    }
+    Ok(())
+}
+
+/// 凭确认令牌结束占用端口的进程，并等待端口可以重新绑定
+pub async fn resolve(token: &str) -> Result<PortResolution, String> {
+    let request = take_token(token, Instant::now())?;
+
+    let pending_kill = request.clone();
+    tokio::task::spawn_blocking(move || kill_owner(&pending_kill))
+        .await
+        .map_err(|e| format!("结束进程失败: {}", e))??;
+
+    // 等待端口释放
+    let deadline = Instant::now() + Duration::from_millis(PORT_RELEASE_TIMEOUT_MS);
+    let mut port_released = false;
+    while Instant::now() < deadline {
+        if find_process_using_port(request.port).is_none() {
+            port_released = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(PORT_POLL_INTERVAL_MS)).await;
+    }
+
+    // 验证端口可以绑定（立即释放，留给 BitBrowser 使用）
+    let bindable = port_released && TcpListener::bind(("127.0.0.1", request.port)).is_ok();
+
+    let message = if bindable {
+        println!("✓ 端口 {} 已释放", request.port);
+        format!("端口 {} 已释放，可以启动 BitBrowser", request.port)
+    } else if port_released {
+        format!("端口 {} 已无进程监听，但暂时无法绑定，请稍后重试", request.port)
+    } else {
+        format!("进程已结束，但端口 {} 仍被占用", request.port)
+    };
+
+    Ok(PortResolution {
+        port: request.port,
+        killed_pid: request.pid,
+        port_released,
+        bindable,
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protected_processes() {
+        let kind = |pid: u32, name: &str, exe_path: Option<&Path>| protection(pid, name, exe_path).map(|(kind, _)| kind);
+        assert_eq!(kind(4, "System", None), Some(ProtectedKind::System));
+        assert_eq!(kind(std::process::id(), "video-toolbox", None), Some(ProtectedKind::CurrentApp));
+        assert_eq!(kind(1234, "svchost.exe", None), Some(ProtectedKind::System));
+        assert_eq!(kind(1234, "BitBrowser.exe", None), Some(ProtectedKind::BitBrowser));
+        assert_eq!(
+            kind(1234, "foo.exe", Some(Path::new("C:\\Windows\\System32\\foo.exe"))),
+            Some(ProtectedKind::System)
+        );
+        assert_eq!(kind(1234, "node.exe", Some(Path::new("C:\\Program Files\\nodejs\\node.exe"))), None);
+    }
+
+    #[test]
+    fn test_token_single_use_and_expiry() {
+        let now = Instant::now();
+        let request = PendingKill {
+            port: 54345,
+            pid: 1234,
+            start_time: 0,
+            expires_at: now + Duration::from_secs(10),
+        };
+        pending().lock().unwrap().insert("ok".to_string(), request.clone());
+        pending().lock().unwrap().insert("old".to_string(), request);
+
+        assert_eq!(take_token("ok", now).unwrap().pid, 1234);
+        assert!(take_token("ok", now).is_err());
+        assert!(take_token("old", now + Duration::from_secs(11)).is_err());
+        assert!(take_token("missing", now).is_err());
+    }
+}
@@ -0,0 +1,286 @@
+/**
+ * BitBrowser Profile
+ * 浏览器配置的字段级更新（读取-修改-写回）
+ *
+ * BitBrowser 的 /browser/update 需要提交完整配置，直接"读取详情 → 修改 → 写回"
+ * 在并发编辑时（例如同步 Cookie 的同时重命名）会互相覆盖。
+ *
+ * 功能：
+ * - 字段级补丁：只修改补丁中的字段，其余字段以最新详情为准
+ * - 同一浏览器的更新串行执行
+ * - 写入后回读校验，发现被覆盖时重试，仍失败则返回冲突错误
+ */
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::bitbrowser_client::{BitBrowserClient, BitBrowserError, BrowserCookie, BrowserProfile};
+
+/// 回读校验失败时的最大尝试次数
+const MAX_PATCH_ATTEMPTS: u32 = 3;
+
+/// 每个浏览器 ID 对应的更新锁
+static PROFILE_LOCKS: OnceLock<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> =
+    OnceLock::new();
+
+/// 浏览器配置的字段级补丁
+#[derive(Debug, Clone, Default)]
+pub struct ProfilePatch {
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ProfilePatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置任意顶层字段
+    pub fn set(mut self, field: &str, value: serde_json::Value) -> Self {
+        self.fields.insert(field.to_string(), value);
+        self
+    }
+
+    /// 设置浏览器名称
+    pub fn name(self, name: &str) -> Self {
+        self.set("name", serde_json::json!(name))
+    }
+
+    /// 替换 Cookie
+    pub fn cookies(self, cookies: &[BrowserCookie]) -> Self {
+        self.set("cookie", serde_json::to_value(cookies).unwrap_or_default())
+    }
+
+    /// 从前端传入的配置对象构建补丁（忽略 id 字段）
+    pub fn from_object(value: serde_json::Value) -> Result<Self, String> {
+        match value {
+            serde_json::Value::Object(mut fields) => {
+                fields.remove("id");
+                if let Some(cookie) = fields.get("cookie") {
+                    BrowserCookie::parse_field(cookie).map_err(|e| e.to_string())?;
+                }
+                Ok(ProfilePatch { fields })
+            }
+            _ => Err("浏览器配置必须是 JSON 对象".to_string()),
+        }
+    }
+
+    /// 补丁中的字段名（用于日志，不输出字段值）
+    pub fn field_names(&self) -> Vec<&str> {
+        self.fields.keys().map(String::as_str).collect()
+    }
+
+    /// 将补丁应用到配置上
+    fn apply(&self, profile: &BrowserProfile) -> Result<BrowserProfile, BitBrowserError> {
+        let mut value = serde_json::to_value(profile)
+            .map_err(|e| BitBrowserError::InvalidResponse(e.to_string()))?;
+
+        if let Some(object) = value.as_object_mut() {
+            for (field, field_value) in &self.fields {
+                object.insert(field.clone(), field_value.clone());
+            }
+        }
+
+        serde_json::from_value(value)
+            .map_err(|e| BitBrowserError::InvalidResponse(format!("补丁字段格式错误: {}", e)))
+    }
+
+    /// 校验回读的配置是否包含补丁中的值，返回第一个不一致的字段
+    ///
+    /// BitBrowser 未回显的字段无法校验，直接跳过；只比较补丁中出现的键，
+    /// BitBrowser 补充的键不视为不一致
+    fn verify(&self, stored: &BrowserProfile) -> Result<(), String> {
+        let stored_value = serde_json::to_value(stored).map_err(|e| e.to_string())?;
+
+        for (field, expected) in &self.fields {
+            if field == "cookie" {
+                let expected = BrowserCookie::parse_field(expected).map_err(|_| field.clone())?;
+                let actual = stored.cookies().map_err(|_| field.clone())?;
+                let all_present = expected.iter().all(|cookie| {
+                    actual
+                        .iter()
+                        .any(|c| c.name == cookie.name && c.value == cookie.value)
+                });
+                if !all_present {
+                    return Err(field.clone());
+                }
+                continue;
+            }
+
+            if let Some(actual) = stored_value.get(field) {
+                if !contains_patch(actual, expected) {
+                    return Err(field.clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// actual 是否包含 expected 中的值
+///
+/// 对象只比较 expected 中的键（null 和未回显的键跳过），数组逐项比较
+fn contains_patch(actual: &serde_json::Value, expected: &serde_json::Value) -> bool {
+    use serde_json::Value;
+
+    match (actual, expected) {
+        (_, Value::Null) => true,
+        (Value::Object(actual), Value::Object(expected)) => expected
+            .iter()
+            .all(|(key, value)| actual.get(key).map_or(true, |actual| contains_patch(actual, value))),
+        (Value::Array(actual), Value::Array(expected)) => {
+            actual.len() == expected.len()
+                && actual.iter().zip(expected).all(|(actual, expected)| contains_patch(actual, expected))
+        }
+        _ => actual == expected,
+    }
+}
+
+/// 获取指定浏览器的更新锁
+fn profile_lock(browser_id: &str) -> Arc<tokio::sync::Mutex<()>> {
+    let locks = PROFILE_LOCKS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut locks = locks.lock().unwrap();
+
+    // 清理已无人持有的锁
+    locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+
+    locks
+        .entry(browser_id.to_string())
+        .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
+        .clone()
+}
+
+/// 对浏览器配置应用字段级补丁
+///
+/// # 参数
+/// - `client`: BitBrowser 客户端
+/// - `browser_id`: 浏览器 ID
+/// - `patch`: 要修改的字段
+///
+/// # 返回
+/// - 写入并校验后的最新配置
+pub async fn patch_browser(
+    client: &BitBrowserClient,
+    browser_id: &str,
+    patch: &ProfilePatch,
+) -> Result<BrowserProfile, BitBrowserError> {
+    let lock = profile_lock(browser_id);
+    let _guard = lock.lock().await;
+
+    let mut conflict_field = String::new();
+
+    for attempt in 1..=MAX_PATCH_ATTEMPTS {
+        // 1. 读取最新配置
+        let current = client.browser_detail(browser_id).await?;
+
+        // 2. 只修改补丁中的字段后写回
+        let updated = patch.apply(&current)?;
+        client.update_browser(&updated).await?;
+
+        // 3. 回读校验
+        let stored = client.browser_detail(browser_id).await?;
+        match patch.verify(&stored) {
+            Ok(()) => return Ok(stored),
+            Err(field) => {
+                println!(
+                    "⚠ 浏览器 {} 的字段 {} 写入后被覆盖，重试 ({}/{})",
+                    browser_id, field, attempt, MAX_PATCH_ATTEMPTS
+                );
+                conflict_field = field;
+            }
+        }
+    }
+
+    Err(BitBrowserError::Conflict(format!(
+        "浏览器 {} 的字段 {} 被并发修改覆盖",
+        browser_id, conflict_field
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_profile() -> BrowserProfile {
+        serde_json::from_value(serde_json::json!({
+            "id": "abc",
+            "name": "#1",
+            "remark": "old",
+            "proxyType": "noproxy",
+            "cookie": "[{\"name\":\"wxuin\",\"value\":\"1\"}]"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_apply_keeps_other_fields() {
+        let patch = ProfilePatch::new().name("#2");
+        let updated = patch.apply(&sample_profile()).unwrap();
+        assert_eq!(updated.name.as_deref(), Some("#2"));
+        assert_eq!(updated.remark.as_deref(), Some("old"));
+        assert_eq!(updated.extra["proxyType"], "noproxy");
+    }
+
+    #[test]
+    fn test_verify_detects_overwrite() {
+        let patch = ProfilePatch::new().name("#2");
+        assert_eq!(patch.verify(&sample_profile()), Err("name".to_string()));
+
+        let stored = patch.apply(&sample_profile()).unwrap();
+        assert!(patch.verify(&stored).is_ok());
+    }
+
+    #[test]
+    fn test_verify_cookie_string_against_array() {
+        let cookies = BrowserCookie::parse_cookie_string("wxuin=1", ".weixin.qq.com");
+        let patch = ProfilePatch::new().cookies(&cookies);
+        assert!(patch.verify(&sample_profile()).is_ok());
+
+        let cookies = BrowserCookie::parse_cookie_string("wxuin=2", ".weixin.qq.com");
+        let patch = ProfilePatch::new().cookies(&cookies);
+        assert_eq!(patch.verify(&sample_profile()), Err("cookie".to_string()));
+    }
+
+    #[test]
+    fn test_verify_compares_patched_keys_only() {
+        let mut stored = sample_profile();
+        stored.extra.insert(
+            "browserFingerPrint".to_string(),
+            serde_json::json!({ "coreVersion": "112", "ostype": "PC", "batchRandom": false }),
+        );
+        stored.extra.insert(
+            "extensions".to_string(),
+            serde_json::json!([{ "id": "ext", "enabled": true, "version": "1.0" }]),
+        );
+
+        // BitBrowser 补充的键不影响校验
+        let patch = ProfilePatch::new()
+            .set("browserFingerPrint", serde_json::json!({ "coreVersion": "112" }))
+            .set("extensions", serde_json::json!([{ "id": "ext", "enabled": true }]));
+        assert!(patch.verify(&stored).is_ok());
+
+        let patch = ProfilePatch::new().set("browserFingerPrint", serde_json::json!({ "coreVersion": "120" }));
+        assert_eq!(patch.verify(&stored), Err("browserFingerPrint".to_string()));
+    }
+
+    #[test]
+    fn test_verify_cookie_patch_as_string() {
+        let patch = ProfilePatch::from_object(serde_json::json!({
+            "cookie": "[{\"name\":\"wxuin\",\"value\":\"2\"}]"
+        }))
+        .unwrap();
+        assert_eq!(patch.verify(&sample_profile()), Err("cookie".to_string()));
+
+        // 无法解析的 Cookie 不会被当作空列表
+        assert!(ProfilePatch::from_object(serde_json::json!({ "cookie": "wxuin=1" })).is_err());
+        let patch = ProfilePatch::new().set("cookie", serde_json::json!("wxuin=1"));
+        assert_eq!(patch.verify(&sample_profile()), Err("cookie".to_string()));
+    }
+
+    #[test]
+    fn test_from_object_ignores_id() {
+        let patch = ProfilePatch::from_object(serde_json::json!({ "id": "x", "remark": "r" })).unwrap();
+        assert!(!patch.fields.contains_key("id"));
+        assert_eq!(patch.field_names(), vec!["remark"]);
+        assert!(ProfilePatch::from_object(serde_json::json!([1])).is_err());
+    }
+}
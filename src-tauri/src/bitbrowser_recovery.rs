@@ -0,0 +1,305 @@
+/**
+ * BitBrowser Recovery
+ * BitBrowser 自动恢复策略（默认关闭）
+ *
+ * 功能：
+ * - 未运行超过宽限时间时自动启动
+ * - 长时间卡在初始化（API 无响应）时结束进程并重新启动
+ * - 启动后超过期限仍未连接时再次尝试，达到最大次数后放弃
+ * - 每个动作都向前端推送事件
+ */
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+
+use crate::bitbrowser_detector::DisconnectReason;
+use crate::bitbrowser_launcher;
+use crate::bitbrowser_shutdown::{self, ShutdownOptions};
+
+/// 前端事件名称
+const EVENT_NAME: &str = "bitbrowser-recovery";
+
+/// 结束进程后等待进程退出的时间（毫秒）
+const KILL_SETTLE_MS: u64 = 2000;
+
+/// 自动恢复策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RecoveryPolicy {
+    /// 是否启用自动恢复
+    pub enabled: bool,
+    /// 未运行持续多久后自动启动（秒）
+    pub not_running_grace_secs: u64,
+    /// 初始化持续多久视为卡住（秒）
+    pub initializing_timeout_secs: u64,
+    /// 恢复动作执行后，多久仍未连接则再次尝试（秒）
+    pub unresponsive_deadline_secs: u64,
+    /// 连接恢复前最多尝试的次数
+    pub max_attempts: u32,
+}
+
+impl RecoveryPolicy {
+    /// 校验策略
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled && self.unresponsive_deadline_secs == 0 {
+            return Err("自动恢复的等待期限必须大于 0 秒".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for RecoveryPolicy {
+    fn default() -> Self {
+        RecoveryPolicy {
+            enabled: false,
+            not_running_grace_secs: 30,
+            initializing_timeout_secs: 180,
+            unresponsive_deadline_secs: 120,
+            max_attempts: 3,
+        }
+    }
+}
+
+/// 恢复动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryAction {
+    /// 启动 BitBrowser
+    Launch,
+    /// 结束 BitBrowser 进程后重新启动
+    Restart,
+    /// 达到最大尝试次数，放弃自动恢复
+    GiveUp,
+    /// 自动恢复后连接已恢复
+    Recovered,
+}
+
+/// 前端事件 Payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryEvent {
+    /// launch / kill / give_up / recovered
+    pub action: String,
+    pub success: bool,
+    pub message: String,
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub timestamp: u64,
+}
+
+/// 自动恢复的决策状态
+#[derive(Debug, Default)]
+pub struct RecoveryTracker {
+    /// 已尝试次数（连接恢复后清零）
+    attempts: u32,
+    /// 当前异常状态的类型及开始时间
+    unhealthy: Option<(&'static str, Instant)>,
+    /// 上次执行恢复动作的时间
+    last_action_at: Option<Instant>,
+    /// 是否已放弃
+    gave_up: bool,
+}
+
+impl RecoveryTracker {
+    /// 已尝试次数
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// 根据最新检测结果决定是否执行恢复动作
+    ///
+    /// # 参数
+    /// - `policy`: 恢复策略
+    /// - `reason`: 断连原因，None 表示已连接
+    /// - `now`: 当前时间
+    pub fn evaluate(
+        &mut self,
+        policy: &RecoveryPolicy,
+        reason: Option<&DisconnectReason>,
+        now: Instant,
+    ) -> Option<RecoveryAction> {
+        let reason = match reason {
+            None => {
+                let recovered = self.attempts > 0;
+                *self = RecoveryTracker::default();
+                return recovered.then_some(RecoveryAction::Recovered);
+            }
+            Some(reason) => reason,
+        };
+
+        // 端口占用、鉴权失败等需要用户处理，重启无济于事
+        let threshold = match reason {
+            DisconnectReason::NotRunning => policy.not_running_grace_secs,
+            DisconnectReason::Initializing => policy.initializing_timeout_secs,
+            _ => {
+                self.unhealthy = None;
+                return None;
+            }
+        };
+
+        let kind = reason.kind();
+        let since = match self.unhealthy {
+            Some((last_kind, since)) if last_kind == kind => since,
+            _ => {
+                self.unhealthy = Some((kind, now));
+                now
+            }
+        };
+
+        if !policy.enabled || self.gave_up {
+            return None;
+        }
+
+        // 已执行过动作时等待其生效，否则等待异常状态持续足够久
+        let waited = match self.last_action_at {
+            Some(last) => now.duration_since(last) >= Duration::from_secs(policy.unresponsive_deadline_secs),
+            None => now.duration_since(since) >= Duration::from_secs(threshold),
+        };
+        if !waited {
+            return None;
+        }
+
+        if self.attempts >= policy.max_attempts {
+            self.gave_up = true;
+            return Some(RecoveryAction::GiveUp);
+        }
+
+        self.attempts += 1;
+        self.last_action_at = Some(now);
+
+        match reason {
+            DisconnectReason::NotRunning => Some(RecoveryAction::Launch),
+            _ => Some(RecoveryAction::Restart),
+        }
+    }
+}
+
+/// 执行恢复动作并推送事件
+///
+/// # 参数
+/// - `app_handle`: Tauri 应用句柄
+/// - `action`: 恢复动作
+/// - `attempt`: 当前尝试次数
+/// - `max_attempts`: 最大尝试次数
+pub async fn execute(app_handle: &tauri::AppHandle, action: RecoveryAction, attempt: u32, max_attempts: u32) {
+    let emit = |action: &str, success: bool, message: String| {
+        println!("{} 自动恢复 [{}] {}", if success { "✓" } else { "⚠" }, action, message);
+        let event = RecoveryEvent {
+            action: action.to_string(),
+            success,
+            message,
+            attempt,
+            max_attempts,
+            timestamp: get_timestamp(),
+        };
+        if let Err(e) = app_handle.emit_all(EVENT_NAME, &event) {
+            eprintln!("⚠ 推送恢复事件失败: {}", e);
+        }
+    };
+
+    match action {
+        RecoveryAction::GiveUp => {
+            emit(
+                "give_up",
+                false,
+                format!("已尝试 {} 次仍无法连接 BitBrowser，停止自动恢复", max_attempts),
+            );
+            return;
+        }
+        RecoveryAction::Recovered => {
+            emit("recovered", true, "BitBrowser 连接已恢复".to_string());
+            return;
+        }
+        RecoveryAction::Restart => {
+            let report = bitbrowser_shutdown::shutdown(&ShutdownOptions::default()).await;
+            emit(
+                "kill",
+                report.success,
+                format!("BitBrowser API 长时间无响应，关闭进程: {}", report.summary()),
+            );
+            tokio::time::sleep(Duration::from_millis(KILL_SETTLE_MS)).await;
+        }
+        RecoveryAction::Launch => {}
+    }
+
+    let result = bitbrowser_launcher::launch(None).await;
+    emit("launch", result.success, result.message);
+}
+
+/// 获取当前时间戳（毫秒）
+fn get_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RecoveryPolicy {
+        RecoveryPolicy {
+            enabled: true,
+            max_attempts: 2,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_disabled_policy_never_acts() {
+        let mut tracker = RecoveryTracker::default();
+        let now = Instant::now();
+        let policy = RecoveryPolicy::default();
+        assert_eq!(tracker.evaluate(&policy, Some(&DisconnectReason::NotRunning), now), None);
+        let later = now + Duration::from_secs(3600);
+        assert_eq!(tracker.evaluate(&policy, Some(&DisconnectReason::NotRunning), later), None);
+    }
+
+    #[test]
+    fn test_launch_then_restart_then_give_up() {
+        let mut tracker = RecoveryTracker::default();
+        let policy = policy();
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+
+        // 宽限时间内不动作
+        assert_eq!(tracker.evaluate(&policy, Some(&DisconnectReason::NotRunning), t0), None);
+        assert_eq!(tracker.evaluate(&policy, Some(&DisconnectReason::NotRunning), at(10)), None);
+        assert_eq!(
+            tracker.evaluate(&policy, Some(&DisconnectReason::NotRunning), at(30)),
+            Some(RecoveryAction::Launch)
+        );
+
+        // 启动后卡在初始化，超过期限后重启
+        assert_eq!(tracker.evaluate(&policy, Some(&DisconnectReason::Initializing), at(60)), None);
+        assert_eq!(
+            tracker.evaluate(&policy, Some(&DisconnectReason::Initializing), at(150)),
+            Some(RecoveryAction::Restart)
+        );
+
+        // 达到最大次数后放弃，且只放弃一次
+        assert_eq!(
+            tracker.evaluate(&policy, Some(&DisconnectReason::Initializing), at(270)),
+            Some(RecoveryAction::GiveUp)
+        );
+        assert_eq!(tracker.evaluate(&policy, Some(&DisconnectReason::Initializing), at(500)), None);
+
+        // 连接恢复后清零
+        assert_eq!(tracker.evaluate(&policy, None, at(510)), Some(RecoveryAction::Recovered));
+        assert_eq!(tracker.attempts(), 0);
+        assert_eq!(tracker.evaluate(&policy, None, at(520)), None);
+    }
+
+    #[test]
+    fn test_ignores_reasons_needing_user_action() {
+        let mut tracker = RecoveryTracker::default();
+        let policy = policy();
+        let reason = DisconnectReason::PortOccupied {
+            process_name: "nginx".to_string(),
+            process_id: 1,
+        };
+        let now = Instant::now();
+        assert_eq!(tracker.evaluate(&policy, Some(&reason), now), None);
+        assert_eq!(tracker.evaluate(&policy, Some(&reason), now + Duration::from_secs(3600)), None);
+    }
+}
@@ -0,0 +1,354 @@
+/**
+ * BitBrowser Search
+ * 后台深度搜索 BitBrowser 安装路径
+ *
+ * 功能：
+ * - 每个搜索根目录（盘符）一个线程并行搜索，任一线程找到即全部停止
+ * - 定期向前端推送进度（已扫描目录数、各根目录当前位置）
+ * - 可随时取消，可由用户指定搜索根目录
+ * - 找到的路径写入缓存，之后启动无需重新搜索
+ */
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+use crate::bitbrowser_cache;
+use crate::bitbrowser_manager::{find_exe_in_directory, get_available_drives};
+use crate::bitbrowser_version::detect_client_version;
+
+/// 前端事件名称
+const EVENT_NAME: &str = "bitbrowser-search-progress";
+
+/// 进度推送间隔（毫秒）
+const PROGRESS_INTERVAL_MS: u64 = 500;
+
+/// 搜索时跳过的目录（小写）
+const SKIPPED_DIRS: &[&str] = &[
+    "windows",
+    "system volume information",
+    "recycler",
+    "$recycle.bin",
+    "proc",
+    "sys",
+    "dev",
+    "node_modules",
+];
+
+/// 搜索选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchOptions {
+    /// 搜索根目录，为空时搜索所有盘符
+    pub roots: Vec<String>,
+    /// 相对根目录的最大搜索深度
+    pub max_depth: usize,
+    /// 超时时间（秒），None 表示不限时
+    pub timeout_secs: Option<u64>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            roots: Vec::new(),
+            max_depth: 4,
+            timeout_secs: Some(300),
+        }
+    }
+}
+
+/// 搜索状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchStatus {
+    Running,
+    Found,
+    NotFound,
+    Cancelled,
+    Timeout,
+}
+
+/// 前端事件 Payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchProgress {
+    pub job_id: String,
+    pub status: SearchStatus,
+    pub scanned_dirs: usize,
+    /// 正在搜索的根目录 -> 当前目录
+    pub current: HashMap<String, String>,
+    pub roots_done: usize,
+    pub roots_total: usize,
+    pub elapsed_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// 搜索线程共享的状态
+#[derive(Default)]
+struct SearchShared {
+    /// 找到结果、取消或超时后置为 true，所有线程停止
+    stop: AtomicBool,
+    /// 用户取消
+    cancelled: AtomicBool,
+    scanned_dirs: AtomicUsize,
+    roots_done: AtomicUsize,
+    current: Mutex<HashMap<String, String>>,
+    found: Mutex<Option<String>>,
+}
+
+/// 正在进行的搜索任务
+struct ActiveSearch {
+    id: String,
+    shared: Arc<SearchShared>,
+}
+
+/// 深度搜索任务控制器（保存在 AppState 中，同一时间只允许一个搜索任务）
+#[derive(Default)]
+pub struct SearchController {
+    active: Arc<Mutex<Option<ActiveSearch>>>,
+}
+
+impl SearchController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 启动后台搜索，返回任务 ID
+    pub fn start(&self, app_handle: tauri::AppHandle, options: SearchOptions) -> Result<String, String> {
+        let mut active = self.active.lock().unwrap();
+        if let Some(job) = active.as_ref() {
+            return Err(format!("已有搜索任务正在进行: {}", job.id));
+        }
+
+        let roots = if options.roots.is_empty() {
+            default_roots()
+        } else {
+            options.roots.clone()
+        };
+        if roots.is_empty() {
+            return Err("没有可搜索的目录".to_string());
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let shared = Arc::new(SearchShared::default());
+        *active = Some(ActiveSearch {
+            id: id.clone(),
+            shared: shared.clone(),
+        });
+
+        let slot = self.active.clone();
+        let job_id = id.clone();
+        tauri::async_runtime::spawn(async move {
+            run_search(&app_handle, &job_id, roots, &options, shared).await;
+
+            let mut active = slot.lock().unwrap();
+            if active.as_ref().map_or(false, |job| job.id == job_id) {
+                *active = None;
+            }
+        });
+
+        Ok(id)
+    }
+
+    /// 取消正在进行的搜索，没有任务时返回 false
+    pub fn cancel(&self) -> bool {
+        match self.active.lock().unwrap().as_ref() {
+            Some(job) => {
+                job.shared.cancelled.store(true, Ordering::Relaxed);
+                job.shared.stop.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// 默认搜索根目录（所有盘符）
+pub fn default_roots() -> Vec<String> {
+    get_available_drives()
+}
+
+/// 执行搜索并推送进度
+async fn run_search(
+    app_handle: &tauri::AppHandle,
+    job_id: &str,
+    roots: Vec<String>,
+    options: &SearchOptions,
+    shared: Arc<SearchShared>,
+) {
+    let started = Instant::now();
+    let roots_total = roots.len();
+    println!("开始深度搜索 BitBrowser（{} 个根目录）...", roots_total);
+
+    let workers = roots.into_iter().map(|root| {
+        let shared = shared.clone();
+        let max_depth = options.max_depth;
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Some(path) = search_root(&root, max_depth, &shared) {
+                let mut found = shared.found.lock().unwrap();
+                if found.is_none() {
+                    *found = Some(path);
+                }
+                shared.stop.store(true, Ordering::Relaxed);
+            }
+            shared.current.lock().unwrap().remove(&root);
+            shared.roots_done.fetch_add(1, Ordering::Relaxed);
+        })
+    });
+    let all_done = futures::future::join_all(workers);
+    tokio::pin!(all_done);
+
+    let deadline = tokio::time::sleep(Duration::from_secs(options.timeout_secs.unwrap_or(0)));
+    tokio::pin!(deadline);
+    let mut ticker = tokio::time::interval(Duration::from_millis(PROGRESS_INTERVAL_MS));
+    let mut timed_out = false;
+
+    loop {
+        tokio::select! {
+            _ = &mut all_done => break,
+            _ = ticker.tick() => {
+                emit_progress(app_handle, job_id, SearchStatus::Running, &shared, roots_total, started);
+            }
+            _ = &mut deadline, if options.timeout_secs.is_some() && !timed_out => {
+                println!("⚠ 深度搜索超时，停止搜索");
+                timed_out = true;
+                shared.stop.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    let found = shared.found.lock().unwrap().clone();
+    let status = if let Some(path) = &found {
+        println!("✓ 从深度搜索找到: {}", path);
+        let version = detect_client_version(path).map(|v| v.version);
+        bitbrowser_cache::store(path, "deep_search", version);
+        SearchStatus::Found
+    } else if shared.cancelled.load(Ordering::Relaxed) {
+        println!("深度搜索已取消");
+        SearchStatus::Cancelled
+    } else if timed_out {
+        SearchStatus::Timeout
+    } else {
+        println!("✗ 深度搜索未找到 BitBrowser");
+        SearchStatus::NotFound
+    };
+
+    emit_progress(app_handle, job_id, status, &shared, roots_total, started);
+}
+
+fn emit_progress(
+    app_handle: &tauri::AppHandle,
+    job_id: &str,
+    status: SearchStatus,
+    shared: &SearchShared,
+    roots_total: usize,
+    started: Instant,
+) {
+    let progress = SearchProgress {
+        job_id: job_id.to_string(),
+        status,
+        scanned_dirs: shared.scanned_dirs.load(Ordering::Relaxed),
+        current: shared.current.lock().unwrap().clone(),
+        roots_done: shared.roots_done.load(Ordering::Relaxed),
+        roots_total,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        path: shared.found.lock().unwrap().clone(),
+    };
+
+    if let Err(e) = app_handle.emit_all(EVENT_NAME, &progress) {
+        eprintln!("⚠ 推送搜索进度失败: {}", e);
+    }
+}
+
+/// 是否跳过该目录（系统目录和隐藏目录）
+fn should_skip(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    lower.starts_with('$') || lower.starts_with('.') || SKIPPED_DIRS.contains(&lower.as_str())
+}
+
+/// 在一个根目录下逐层搜索（深度优先，每个目录检查一次是否需要停止）
+fn search_root(root: &str, max_depth: usize, shared: &SearchShared) -> Option<String> {
+    let mut stack = vec![(PathBuf::from(root), 0usize)];
+
+    while let Some((dir, depth)) = stack.pop() {
+        if shared.stop.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        shared.scanned_dirs.fetch_add(1, Ordering::Relaxed);
+        shared
+            .current
+            .lock()
+            .unwrap()
+            .insert(root.to_string(), dir.to_string_lossy().to_string());
+
+        if let Some(exe_path) = find_exe_in_directory(&dir) {
+            return Some(exe_path);
+        }
+
+        if depth >= max_depth {
+            continue;
+        }
+
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            // 不跟随符号链接，避免循环
+            let is_dir = entry.file_type().map_or(false, |t| t.is_dir());
+            if is_dir && !should_skip(&entry.file_name().to_string_lossy()) {
+                stack.push((entry.path(), depth + 1));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_root_finds_nested_exe() {
+        let root = std::env::temp_dir().join(format!("bb-search-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let install_dir = root.join("Software").join("BitBrowser");
+        fs::create_dir_all(&install_dir).unwrap();
+        fs::create_dir_all(root.join(".hidden").join("BitBrowser")).unwrap();
+
+        let exe_name = if cfg!(target_os = "windows") { "BitBrowser.exe" } else { "BitBrowser" };
+        fs::write(install_dir.join(exe_name), b"").unwrap();
+
+        let root_str = root.to_string_lossy().to_string();
+        let shared = SearchShared::default();
+        assert_eq!(search_root(&root_str, 1, &shared), None);
+
+        let shared = SearchShared::default();
+        let found = search_root(&root_str, 2, &shared).unwrap();
+        assert!(found.ends_with(exe_name));
+        assert!(shared.scanned_dirs.load(Ordering::Relaxed) >= 3);
+
+        // 停止信号
+        let shared = SearchShared::default();
+        shared.stop.store(true, Ordering::Relaxed);
+        assert_eq!(search_root(&root_str, 2, &shared), None);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_should_skip() {
+        assert!(should_skip("Windows"));
+        assert!(should_skip("$Recycle.Bin"));
+        assert!(should_skip(".cache"));
+        assert!(!should_skip("Program Files"));
+    }
+}
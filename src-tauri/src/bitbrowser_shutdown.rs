@@ -0,0 +1,493 @@
+/**
+ * BitBrowser Shutdown
+ * 优雅地关闭 BitBrowser
+ *
+ * 流程：
+ * 1. 通过 /browser/pids/all 获取已打开的浏览器窗口，逐个调用 /browser/close 关闭
+ * 2. 等待窗口进程退出
+ * 3. 请求 BitBrowser 主进程退出（Unix 发送 SIGTERM，Windows 使用不带 /F 的 taskkill）
+ * 4. 超时后仍未退出的进程才强制结束
+ *
+ * 每个进程的处理结果都记录在 ShutdownReport 中。
+ */
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, System};
+
+use crate::bitbrowser_client::BitBrowserClient;
+use crate::bitbrowser_manager::is_bitbrowser_process;
+
+/// 等待进程退出时的轮询间隔（毫秒）
+const EXIT_POLL_MS: u64 = 300;
+
+/// 强制结束后确认进程退出的等待时间（毫秒）
+const FORCE_KILL_SETTLE_MS: u64 = 2000;
+
+/// 关闭选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShutdownOptions {
+    /// 关闭浏览器窗口后等待其退出的时间（秒），包括调用 API 的时间
+    pub profile_timeout_secs: u64,
+    /// 请求主进程退出后等待的时间（秒），超时后强制结束
+    pub terminate_timeout_secs: u64,
+}
+
+impl Default for ShutdownOptions {
+    fn default() -> Self {
+        ShutdownOptions {
+            profile_timeout_secs: 15,
+            terminate_timeout_secs: 10,
+        }
+    }
+}
+
+/// 进程角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessRole {
+    /// BitBrowser 主进程（父进程不是 BitBrowser）
+    Main,
+    /// 已打开的浏览器窗口
+    Profile,
+    /// 其他 BitBrowser 子进程
+    Child,
+}
+
+/// 进程处理结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessOutcome {
+    /// 通过 /browser/close 关闭
+    Closed,
+    /// 请求退出后正常退出
+    Exited,
+    /// 超时后被强制结束
+    ForceKilled,
+    /// 强制结束后仍在运行
+    StillRunning,
+}
+
+/// 单个进程的处理报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessReport {
+    pub pid: u32,
+    pub name: String,
+    pub role: ProcessRole,
+    /// 浏览器窗口对应的浏览器 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub browser_id: Option<String>,
+    pub outcome: ProcessOutcome,
+}
+
+/// 关闭报告
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShutdownReport {
+    /// 所有进程均已退出
+    pub success: bool,
+    pub processes: Vec<ProcessReport>,
+    /// 关闭浏览器窗口时的错误（浏览器 ID 或 API 阶段 -> 错误信息）
+    pub profile_errors: Vec<(String, String)>,
+    pub elapsed_ms: u64,
+}
+
+impl ShutdownReport {
+    /// 汇总信息
+    pub fn summary(&self) -> String {
+        if self.processes.is_empty() {
+            return "未找到 BitBrowser 进程".to_string();
+        }
+
+        let count = |outcome: ProcessOutcome| {
+            self.processes
+                .iter()
+                .filter(|p| p.outcome == outcome)
+                .count()
+        };
+        let mut summary = format!(
+            "已关闭 {} 个窗口，{} 个进程正常退出，{} 个进程被强制结束",
+            count(ProcessOutcome::Closed),
+            count(ProcessOutcome::Exited),
+            count(ProcessOutcome::ForceKilled)
+        );
+        let still_running = count(ProcessOutcome::StillRunning);
+        if still_running > 0 {
+            summary.push_str(&format!("，{} 个进程仍在运行", still_running));
+        }
+        summary
+    }
+}
+
+/// 进程快照中的一项
+#[derive(Debug, Clone)]
+struct ProcessEntry {
+    pid: u32,
+    parent: Option<u32>,
+    name: String,
+}
+
+/// 需要关闭的进程
+#[derive(Debug, Clone)]
+struct Target {
+    pid: u32,
+    name: String,
+    role: ProcessRole,
+    browser_id: Option<String>,
+}
+
+/// 获取当前进程快照
+fn process_snapshot() -> Vec<ProcessEntry> {
+    let mut system = System::new();
+    system.refresh_processes();
+
+    system
+        .processes()
+        .iter()
+        .map(|(pid, process)| ProcessEntry {
+            pid: pid.as_u32(),
+            parent: process.parent().map(|p| p.as_u32()),
+            name: process.name().to_string(),
+        })
+        .collect()
+}
+
+/// 根据进程快照和已打开的浏览器窗口确定要关闭的进程
+///
+/// 名称匹配 BitBrowser 且父进程不是 BitBrowser 的为主进程，其余为子进程；
+/// 浏览器窗口进程（名称可能是内核的 chrome）按 API 返回的 PID 加入，
+/// 不在快照中的（快照之后启动的窗口）同样加入。
+fn classify_targets(entries: &[ProcessEntry], profile_pids: &HashMap<String, u32>) -> Vec<Target> {
+    let names: HashMap<u32, &str> = entries.iter().map(|e| (e.pid, e.name.as_str())).collect();
+    let profile_by_pid: HashMap<u32, &String> =
+        profile_pids.iter().map(|(id, pid)| (*pid, id)).collect();
+
+    let mut targets: Vec<Target> = entries
+        .iter()
+        .filter_map(|entry| {
+            if let Some(browser_id) = profile_by_pid.get(&entry.pid) {
+                return Some(Target {
+                    pid: entry.pid,
+                    name: entry.name.clone(),
+                    role: ProcessRole::Profile,
+                    browser_id: Some(browser_id.to_string()),
+                });
+            }
+            if !is_bitbrowser_process(&entry.name) {
+                return None;
+            }
+
+            let parent_is_bitbrowser = entry
+                .parent
+                .and_then(|parent| names.get(&parent))
+                .map_or(false, |name| is_bitbrowser_process(name));
+            Some(Target {
+                pid: entry.pid,
+                name: entry.name.clone(),
+                role: if parent_is_bitbrowser {
+                    ProcessRole::Child
+                } else {
+                    ProcessRole::Main
+                },
+                browser_id: None,
+            })
+        })
+        .collect();
+
+    for (browser_id, pid) in profile_pids {
+        if !names.contains_key(pid) {
+            targets.push(Target {
+                pid: *pid,
+                name: "浏览器窗口".to_string(),
+                role: ProcessRole::Profile,
+                browser_id: Some(browser_id.clone()),
+            });
+        }
+    }
+
+    targets.sort_by_key(|t| t.pid);
+    targets
+}
+
+/// 等待浏览器窗口进程退出，返回已退出窗口的处理结果
+async fn wait_for_profiles(targets: &[Target], timeout: Duration) -> HashMap<u32, ProcessOutcome> {
+    let profile_set: HashSet<u32> = targets
+        .iter()
+        .filter(|t| t.role == ProcessRole::Profile)
+        .map(|t| t.pid)
+        .collect();
+    if profile_set.is_empty() {
+        return HashMap::new();
+    }
+
+    let alive = wait_for_exit(&profile_set, timeout).await;
+    println!(
+        "✓ {} 个浏览器窗口已关闭，{} 个未退出",
+        profile_set.len() - alive.len(),
+        alive.len()
+    );
+    profile_set
+        .difference(&alive)
+        .map(|pid| (*pid, ProcessOutcome::Closed))
+        .collect()
+}
+
+/// 进程是否仍在运行
+fn is_alive(system: &mut System, pid: u32) -> bool {
+    system.refresh_process(Pid::from_u32(pid))
+}
+
+/// 等待进程退出，返回超时后仍在运行的进程
+async fn wait_for_exit(pids: &HashSet<u32>, timeout: Duration) -> HashSet<u32> {
+    let deadline = Instant::now() + timeout;
+    let mut system = System::new();
+
+    loop {
+        let alive: HashSet<u32> = pids
+            .iter()
+            .copied()
+            .filter(|pid| is_alive(&mut system, *pid))
+            .collect();
+        if alive.is_empty() || Instant::now() >= deadline {
+            return alive;
+        }
+        tokio::time::sleep(Duration::from_millis(EXIT_POLL_MS)).await;
+    }
+}
+
+/// 请求进程正常退出
+#[cfg(not(target_os = "windows"))]
+fn request_terminate(pid: u32) -> bool {
+    let mut system = System::new();
+    if !is_alive(&mut system, pid) {
+        return true;
+    }
+    system
+        .process(Pid::from_u32(pid))
+        .and_then(|process| process.kill_with(sysinfo::Signal::Term))
+        .unwrap_or(false)
+}
+
+/// 请求进程正常退出（不带 /F 的 taskkill 会向窗口发送关闭消息）
+#[cfg(target_os = "windows")]
+fn request_terminate(pid: u32) -> bool {
+    std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string()])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// 强制结束进程
+fn force_kill(pid: u32) -> bool {
+    let mut system = System::new();
+    if !is_alive(&mut system, pid) {
+        return true;
+    }
+    system
+        .process(Pid::from_u32(pid))
+        .map_or(false, |process| process.kill())
+}
+
+/// 通过 API 关闭所有已打开的浏览器窗口
+///
+/// 返回已打开窗口的浏览器 ID -> PID 和关闭失败的错误
+async fn close_profiles() -> (HashMap<String, u32>, Vec<(String, String)>) {
+    let client = match BitBrowserClient::connect().await {
+        Ok(client) => client,
+        Err(e) => return (HashMap::new(), vec![("api".to_string(), e.to_string())]),
+    };
+
+    let profile_pids = match client.open_browser_pids().await {
+        Ok(pids) => pids,
+        Err(e) => return (HashMap::new(), vec![("api".to_string(), e.to_string())]),
+    };
+
+    let results = futures::future::join_all(profile_pids.keys().map(|id| {
+        let client = client.clone();
+        async move { (id.clone(), client.close_browser(id).await) }
+    }))
+    .await;
+
+    let errors = results
+        .into_iter()
+        .filter_map(|(id, result)| result.err().map(|e| (id, e.to_string())))
+        .collect();
+    (profile_pids, errors)
+}
+
+/// 优雅地关闭 BitBrowser
+pub async fn shutdown(options: &ShutdownOptions) -> ShutdownReport {
+    let started = Instant::now();
+    let profile_timeout = Duration::from_secs(options.profile_timeout_secs);
+    let mut report = ShutdownReport::default();
+    // 关闭窗口之前获取进程快照，很快退出的窗口进程也能记录名称
+    let snapshot = process_snapshot();
+
+    // 1. 关闭浏览器窗口（API 无响应时跳过）
+    let profile_pids = match tokio::time::timeout(profile_timeout, close_profiles()).await {
+        Ok((pids, errors)) => {
+            report.profile_errors = errors;
+            pids
+        }
+        Err(_) => {
+            report
+                .profile_errors
+                .push(("api".to_string(), "BitBrowser API 无响应".to_string()));
+            HashMap::new()
+        }
+    };
+    for (id, error) in &report.profile_errors {
+        println!("⚠ 关闭浏览器窗口失败（{}）: {}", id, error);
+    }
+
+    let targets = classify_targets(&snapshot, &profile_pids);
+    if targets.is_empty() {
+        println!("未找到 BitBrowser 进程");
+        report.elapsed_ms = started.elapsed().as_millis() as u64;
+        return report;
+    }
+
+    // 2. 等待浏览器窗口退出
+    let remaining_time = profile_timeout.saturating_sub(started.elapsed());
+    let mut outcomes = wait_for_profiles(&targets, remaining_time).await;
+
+    // 3. 请求主进程退出
+    for target in targets.iter().filter(|t| t.role == ProcessRole::Main) {
+        if request_terminate(target.pid) {
+            println!("✓ 已请求 BitBrowser 进程退出: {} (PID {})", target.name, target.pid);
+        } else {
+            println!("⚠ 请求进程退出失败: {} (PID {})", target.name, target.pid);
+        }
+    }
+
+    let pending: HashSet<u32> = targets
+        .iter()
+        .map(|t| t.pid)
+        .filter(|pid| !outcomes.contains_key(pid))
+        .collect();
+    let alive = wait_for_exit(&pending, Duration::from_secs(options.terminate_timeout_secs)).await;
+    for pid in pending.difference(&alive) {
+        outcomes.insert(*pid, ProcessOutcome::Exited);
+    }
+
+    // 4. 超时后强制结束
+    if !alive.is_empty() {
+        println!("⚠ {} 个进程在超时后仍未退出，强制结束", alive.len());
+        for pid in &alive {
+            force_kill(*pid);
+        }
+        let still_running = wait_for_exit(&alive, Duration::from_millis(FORCE_KILL_SETTLE_MS)).await;
+        for pid in &alive {
+            let outcome = if still_running.contains(pid) {
+                ProcessOutcome::StillRunning
+            } else {
+                ProcessOutcome::ForceKilled
+            };
+            outcomes.insert(*pid, outcome);
+        }
+    }
+
+    report.processes = targets
+        .into_iter()
+        .map(|target| ProcessReport {
+            outcome: outcomes
+                .get(&target.pid)
+                .copied()
+                .unwrap_or(ProcessOutcome::StillRunning),
+            pid: target.pid,
+            name: target.name,
+            role: target.role,
+            browser_id: target.browser_id,
+        })
+        .collect();
+    report.success = report
+        .processes
+        .iter()
+        .all(|p| p.outcome != ProcessOutcome::StillRunning);
+    report.elapsed_ms = started.elapsed().as_millis() as u64;
+
+    println!("{}", report.summary());
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pid: u32, parent: Option<u32>, name: &str) -> ProcessEntry {
+        ProcessEntry {
+            pid,
+            parent,
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_classify_targets() {
+        let entries = vec![
+            entry(1, None, "explorer.exe"),
+            entry(10, Some(1), "BitBrowser.exe"),
+            entry(11, Some(10), "BitBrowser.exe"),
+            entry(20, Some(10), "chrome.exe"),
+            entry(30, Some(1), "chrome.exe"),
+        ];
+        let profiles = HashMap::from([("profile-a".to_string(), 20)]);
+
+        let targets = classify_targets(&entries, &profiles);
+        let roles: Vec<(u32, ProcessRole)> = targets.iter().map(|t| (t.pid, t.role)).collect();
+        assert_eq!(
+            roles,
+            vec![
+                (10, ProcessRole::Main),
+                (11, ProcessRole::Child),
+                (20, ProcessRole::Profile),
+            ]
+        );
+        assert_eq!(targets[2].browser_id.as_deref(), Some("profile-a"));
+    }
+
+    #[tokio::test]
+    async fn test_exited_profile_is_reported_closed() {
+        // 窗口进程关闭后已不在快照中
+        let gone_pid = u32::MAX - 7;
+        let entries = vec![entry(10, None, "BitBrowser.exe")];
+        let profiles = HashMap::from([("profile-a".to_string(), gone_pid)]);
+
+        let targets = classify_targets(&entries, &profiles);
+        let profile = targets.iter().find(|t| t.pid == gone_pid).unwrap();
+        assert_eq!(profile.role, ProcessRole::Profile);
+        assert_eq!(profile.browser_id.as_deref(), Some("profile-a"));
+
+        let outcomes = wait_for_profiles(&targets, Duration::from_millis(100)).await;
+        assert_eq!(outcomes.get(&gone_pid), Some(&ProcessOutcome::Closed));
+        assert!(!outcomes.contains_key(&10));
+    }
+
+    #[test]
+    fn test_report_summary() {
+        let process = |pid, outcome| ProcessReport {
+            pid,
+            name: "BitBrowser".to_string(),
+            role: ProcessRole::Main,
+            browser_id: None,
+            outcome,
+        };
+        let report = ShutdownReport {
+            success: false,
+            processes: vec![
+                process(1, ProcessOutcome::Closed),
+                process(2, ProcessOutcome::Exited),
+                process(3, ProcessOutcome::StillRunning),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            report.summary(),
+            "已关闭 1 个窗口，1 个进程正常退出，0 个进程被强制结束，1 个进程仍在运行"
+        );
+        assert_eq!(ShutdownReport::default().summary(), "未找到 BitBrowser 进程");
+    }
+}
@@ -0,0 +1,362 @@
+/**
+ * BitBrowser Version
+ * 检测 BitBrowser 客户端版本和浏览器内核版本，并按兼容表决定启动参数和 API 字段
+ *
+ * 客户端版本来源：
+ * - Windows：可执行文件 .rsrc 段中的 VS_VERSION_INFO
+ * - macOS：应用包 Info.plist 的 CFBundleShortVersionString
+ * - 其他：文件名中的版本号（例如 BitBrowser-4.2.1-x86_64.AppImage）
+ *
+ * 内核版本来源：浏览器配置的 coreVersion 字段（Chrome 主版本号）
+ */
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::bitbrowser_client::BrowserProfile;
+
+/// VS_FIXEDFILEINFO 签名
+const FIXED_FILE_INFO_SIGNATURE: [u8; 4] = [0xBD, 0x04, 0xEF, 0xFE];
+
+/// 读取 PE 头部的字节数（足够覆盖 DOS 头、PE 头和节表）
+const PE_HEADER_READ_SIZE: usize = 4096;
+
+/// 最多读取的 .rsrc 段大小
+const MAX_RSRC_SIZE: usize = 64 * 1024 * 1024;
+
+/// 客户端版本
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientVersion {
+    pub version: String,
+    /// 版本来源：exe_metadata / info_plist / file_name
+    pub source: String,
+}
+
+/// 兼容规则：浏览器内核版本落在区间内时生效
+struct CompatRule {
+    id: &'static str,
+    /// 最低内核版本（含），None 表示不限
+    min_core: Option<u32>,
+    /// 最高内核版本（含），None 表示不限
+    max_core: Option<u32>,
+    /// 追加的启动参数
+    launch_args: &'static [&'static str],
+    /// /browser/open 请求中设置的布尔字段
+    api_fields: &'static [(&'static str, bool)],
+    description: &'static str,
+}
+
+/// 兼容表
+///
+/// 内核版本未知时按最新内核处理（这些参数对旧内核无副作用）
+const COMPAT_RULES: &[CompatRule] = &[
+    CompatRule {
+        id: "extension_center",
+        min_core: None,
+        max_core: None,
+        launch_args: &[],
+        api_fields: &[("loadExtensions", true)],
+        description: "加载扩展中心已启用的扩展",
+    },
+    CompatRule {
+        id: "load_extension_switch",
+        min_core: Some(137),
+        max_core: None,
+        launch_args: &["--disable-features=DisableLoadExtensionCommandLineSwitch"],
+        api_fields: &[],
+        description: "Chrome 137 起默认禁用 --load-extension，需要重新启用",
+    },
+];
+
+/// 兼容表的计算结果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Compatibility {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub core_version: Option<u32>,
+    pub launch_args: Vec<String>,
+    pub api_fields: serde_json::Map<String, serde_json::Value>,
+    /// 生效的规则（id 和说明）
+    pub applied_rules: Vec<(String, String)>,
+}
+
+impl Compatibility {
+    /// 读取布尔类型的 API 字段
+    pub fn api_flag(&self, field: &str) -> Option<bool> {
+        self.api_fields.get(field).and_then(|v| v.as_bool())
+    }
+}
+
+/// 按内核版本计算启动参数和 API 字段
+pub fn compatibility_for(core_version: Option<u32>) -> Compatibility {
+    let mut compat = Compatibility {
+        core_version,
+        ..Default::default()
+    };
+
+    for rule in COMPAT_RULES {
+        let matches = match core_version {
+            Some(core) => {
+                rule.min_core.map_or(true, |min| core >= min)
+                    && rule.max_core.map_or(true, |max| core <= max)
+            }
+            None => rule.max_core.is_none(),
+        };
+        if !matches {
+            continue;
+        }
+
+        compat
+            .launch_args
+            .extend(rule.launch_args.iter().map(|a| a.to_string()));
+        for (field, value) in rule.api_fields {
+            compat
+                .api_fields
+                .insert(field.to_string(), serde_json::Value::Bool(*value));
+        }
+        compat
+            .applied_rules
+            .push((rule.id.to_string(), rule.description.to_string()));
+    }
+
+    compat
+}
+
+/// 读取浏览器配置的内核主版本号（coreVersion 或 browserFingerPrint.coreVersion）
+pub fn profile_core_version(profile: &BrowserProfile) -> Option<u32> {
+    let value = profile.extra.get("coreVersion").or_else(|| {
+        profile
+            .extra
+            .get("browserFingerPrint")
+            .and_then(|fp| fp.get("coreVersion"))
+    })?;
+
+    match value {
+        serde_json::Value::Number(n) => n.as_u64().map(|v| v as u32),
+        serde_json::Value::String(s) => major_version(s),
+        _ => None,
+    }
+}
+
+/// 解析版本字符串的主版本号（"137.0.7151.55" -> 137）
+pub fn major_version(version: &str) -> Option<u32> {
+    version
+        .trim()
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// 检测 BitBrowser 客户端版本
+pub fn detect_client_version(exe_path: &str) -> Option<ClientVersion> {
+    let path = Path::new(exe_path);
+
+    if let Some(version) = read_pe_version(path) {
+        return Some(ClientVersion {
+            version,
+            source: "exe_metadata".to_string(),
+        });
+    }
+
+    if let Some(version) = read_bundle_version(path) {
+        return Some(ClientVersion {
+            version,
+            source: "info_plist".to_string(),
+        });
+    }
+
+    let file_name = path.file_name()?.to_string_lossy();
+    version_from_file_name(&file_name).map(|version| ClientVersion {
+        version,
+        source: "file_name".to_string(),
+    })
+}
+
+/// 从 PE 文件的 .rsrc 段读取文件版本
+fn read_pe_version(path: &Path) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    let mut header = vec![0u8; PE_HEADER_READ_SIZE];
+    let read = file.read(&mut header).ok()?;
+    header.truncate(read);
+
+    let (offset, size) = rsrc_section(&header)?;
+    let mut rsrc = vec![0u8; size.min(MAX_RSRC_SIZE)];
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    file.read_exact(&mut rsrc).ok()?;
+
+    fixed_file_version(&rsrc)
+}
+
+/// 解析 PE 头部的节表，返回 .rsrc 段在文件中的偏移和大小
+fn rsrc_section(header: &[u8]) -> Option<(u64, usize)> {
+    let u16_at = |o: usize| header.get(o..o + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let u32_at = |o: usize| {
+        header
+            .get(o..o + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+
+    if header.get(0..2)? != b"MZ" {
+        return None;
+    }
+    let pe = u32_at(0x3C)? as usize;
+    if header.get(pe..pe + 4)? != b"PE\0\0" {
+        return None;
+    }
+
+    let section_count = u16_at(pe + 6)? as usize;
+    let optional_header_size = u16_at(pe + 20)? as usize;
+    let table = pe + 24 + optional_header_size;
+
+    (0..section_count).find_map(|i| {
+        let section = table + i * 40;
+        let name = header.get(section..section + 8)?;
+        if !name.starts_with(b".rsrc") {
+            return None;
+        }
+        let size = u32_at(section + 16)? as usize;
+        let offset = u32_at(section + 20)? as u64;
+        Some((offset, size))
+    })
+}
+
+/// 在资源数据中查找 VS_VERSION_INFO 的 VS_FIXEDFILEINFO，返回文件版本
+fn fixed_file_version(rsrc: &[u8]) -> Option<String> {
+    let key: Vec<u8> = "VS_VERSION_INFO"
+        .encode_utf16()
+        .flat_map(|u| u.to_le_bytes())
+        .collect();
+
+    let key_pos = rsrc.windows(key.len()).position(|w| w == key.as_slice())?;
+    let search = &rsrc[key_pos + key.len()..];
+    let sig_pos = search
+        .windows(4)
+        .take(16)
+        .position(|w| w == FIXED_FILE_INFO_SIGNATURE)?;
+
+    let info = &search[sig_pos..];
+    let u32_at = |o: usize| {
+        info.get(o..o + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    // dwSignature, dwStrucVersion, dwFileVersionMS, dwFileVersionLS
+    let ms = u32_at(8)?;
+    let ls = u32_at(12)?;
+
+    Some(format!("{}.{}.{}.{}", ms >> 16, ms & 0xFFFF, ls >> 16, ls & 0xFFFF))
+}
+
+/// 从 .app 应用包的 Info.plist 读取版本
+fn read_bundle_version(exe_path: &Path) -> Option<String> {
+    // <App>.app/Contents/MacOS/<exe> -> <App>.app/Contents/Info.plist
+    let contents = exe_path.parent()?.parent()?;
+    let plist = std::fs::read_to_string(contents.join("Info.plist")).ok()?;
+    plist_string(&plist, "CFBundleShortVersionString")
+}
+
+/// 读取 XML plist 中指定 key 的字符串值
+pub(crate) fn plist_string(plist: &str, key: &str) -> Option<String> {
+    let after_key = plist.split(&format!("<key>{}</key>", key)).nth(1)?;
+    let value = after_key.trim_start().strip_prefix("<string>")?;
+    let end = value.find("</string>")?;
+    Some(value[..end].trim().to_string()).filter(|v| !v.is_empty())
+}
+
+/// 从文件名中提取版本号（至少两段数字）
+fn version_from_file_name(file_name: &str) -> Option<String> {
+    file_name
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .map(|part| part.trim_matches('.'))
+        .find(|part| part.split('.').filter(|p| !p.is_empty()).count() >= 2)
+        .map(|part| part.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compatibility_table() {
+        let old = compatibility_for(Some(112));
+        assert!(old.launch_args.is_empty());
+        assert_eq!(old.api_flag("loadExtensions"), Some(true));
+
+        let new = compatibility_for(Some(137));
+        assert_eq!(
+            new.launch_args,
+            vec!["--disable-features=DisableLoadExtensionCommandLineSwitch"]
+        );
+
+        // 版本未知时按最新内核处理
+        let unknown = compatibility_for(None);
+        assert_eq!(unknown.launch_args, new.launch_args);
+        assert_eq!(unknown.applied_rules.len(), 2);
+    }
+
+    #[test]
+    fn test_profile_core_version() {
+        let profile: BrowserProfile = serde_json::from_value(serde_json::json!({
+            "id": "a",
+            "browserFingerPrint": { "coreVersion": "137" }
+        }))
+        .unwrap();
+        assert_eq!(profile_core_version(&profile), Some(137));
+
+        let profile: BrowserProfile =
+            serde_json::from_value(serde_json::json!({ "id": "b", "coreVersion": 112 })).unwrap();
+        assert_eq!(profile_core_version(&profile), Some(112));
+
+        assert_eq!(major_version("Chrome/130.0.6723.70"), Some(130));
+    }
+
+    #[test]
+    fn test_pe_version_resource() {
+        // 最小 PE：DOS 头 → PE 头（无可选头）→ 一个 .rsrc 节
+        let mut pe = vec![0u8; 0x200];
+        pe[0..2].copy_from_slice(b"MZ");
+        pe[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        pe[0x40..0x44].copy_from_slice(b"PE\0\0");
+        pe[0x46..0x48].copy_from_slice(&1u16.to_le_bytes());
+        let section = 0x40 + 24;
+        pe[section..section + 5].copy_from_slice(b".rsrc");
+
+        let mut rsrc = vec![0u8; 6];
+        rsrc.extend("VS_VERSION_INFO".encode_utf16().flat_map(|u| u.to_le_bytes()));
+        rsrc.extend_from_slice(&[0, 0, 0, 0]);
+        rsrc.extend_from_slice(&FIXED_FILE_INFO_SIGNATURE);
+        rsrc.extend_from_slice(&0x0001_0000u32.to_le_bytes());
+        rsrc.extend_from_slice(&((4u32 << 16) | 2).to_le_bytes());
+        rsrc.extend_from_slice(&((1u32 << 16) | 7).to_le_bytes());
+
+        pe[section + 16..section + 20].copy_from_slice(&(rsrc.len() as u32).to_le_bytes());
+        pe[section + 20..section + 24].copy_from_slice(&0x200u32.to_le_bytes());
+
+        assert_eq!(rsrc_section(&pe), Some((0x200, rsrc.len())));
+        assert_eq!(fixed_file_version(&rsrc).as_deref(), Some("4.2.1.7"));
+        assert_eq!(rsrc_section(b"not a pe"), None);
+
+        let path = std::env::temp_dir().join(format!("bb-version-{}.exe", std::process::id()));
+        pe.extend_from_slice(&rsrc);
+        std::fs::write(&path, &pe).unwrap();
+        let version = detect_client_version(&path.to_string_lossy()).unwrap();
+        assert_eq!(version.version, "4.2.1.7");
+        assert_eq!(version.source, "exe_metadata");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_other_version_sources() {
+        assert_eq!(
+            version_from_file_name("BitBrowser-4.2.1-x86_64.AppImage").as_deref(),
+            Some("4.2.1")
+        );
+        assert_eq!(version_from_file_name("BitBrowser.exe"), None);
+
+        let plist = "<key>CFBundleShortVersionString</key>\n<string>4.3.0</string>";
+        assert_eq!(plist_string(plist, "CFBundleShortVersionString").as_deref(), Some("4.3.0"));
+    }
+}
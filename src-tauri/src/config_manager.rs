@@ -2,14 +2,24 @@
  * 配置管理模块
  * 统一管理所有应用配置，使用JSON文件持久化
  */
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tauri::Manager;
 
-// 配置文件路径
-fn get_config_path() -> PathBuf {
+use crate::bitbrowser_launch_profile::LaunchProfile;
+use crate::bitbrowser_recovery::RecoveryPolicy;
+use crate::config_schema::{self, SettingDef};
+use crate::config_secrets::{self, KeyMode, SecretKey, SecretsHeader};
+use crate::config_store::{self, LoadReport};
+
+// 应用配置目录
+pub fn get_config_dir() -> PathBuf {
     let app_data_dir = dirs::data_local_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("com.toolbox.dev");
@@ -17,9 +27,12 @@ fn get_config_path() -> PathBuf {
     // 确保目录存在
     fs::create_dir_all(&app_data_dir).ok();
 
-    app_data_dir.join("settings.json")
+    app_data_dir
 }
 
+// 配置文件名
+const CONFIG_FILE_NAME: &str = "settings.json";
+
 // 账号信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -38,31 +51,52 @@ pub struct AccountInfo {
 // 设计原则：
 // - ✅ 只缓存不常变动的基本信息：nickname, avatar, finderUsername 等
 // - ❌ 不缓存动态状态，实时从云端获取：loginMethod, cookieStatus 等
+//   （旧版本存储的 loginMethod / loginTime / lastSyncTime 由 v0 → v1 迁移移除）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AccountData {
     pub account_info: AccountInfo,
     pub updated_at: String,
-    // ✅ 以下动态数据改为可选（已废弃，不再使用）：
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub login_method: Option<String>, // @deprecated 从云端实时获取
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub login_time: Option<i64>, // @deprecated 不再存储
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub link_token: Option<String>, // 永久链接Token（用于删除云端链接和恢复订阅）
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub last_sync_time: Option<i64>, // @deprecated 不再存储
+    pub link_token: Option<String>, // 永久链接Token（用于删除云端链接和恢复订阅，加密保存）
+}
+
+// 需要加密保存的敏感字段在 settings.json 中的字段名（与 sensitive_fields 对应）
+const SENSITIVE_FIELD_NAMES: &[&str] = &["linkToken"];
+
+impl AccountData {
+    // 需要加密保存的敏感字段（新增 Cookie 等敏感字段时在这里和 SENSITIVE_FIELD_NAMES 中登记）
+    fn sensitive_fields(&mut self) -> [&mut Option<String>; 1] {
+        [&mut self.link_token]
+    }
+}
+
+// 配置文件被外部修改时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalEditPolicy {
+    // 合并外部修改，双方都修改的字段保留内存中的值
+    #[default]
+    Merge,
+    // 以文件内容为准，丢弃内存中冲突的修改
+    Reload,
 }
 
 // 完整配置结构
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AppConfig {
+    // 配置文件结构版本（见 SCHEMA_VERSION）
+    #[serde(default)]
+    pub schema_version: u32,
+
     // 基础配置
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bitbrowser_path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bitbrowser_api: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitbrowser_api_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub username: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub filter_my_accounts: Option<bool>,
@@ -72,141 +106,1012 @@ pub struct AppConfig {
     pub test_persistence_mode: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bitbrowser_vip_mode: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitbrowser_launch: Option<LaunchProfile>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitbrowser_recovery: Option<RecoveryPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_edit_policy: Option<ExternalEditPolicy>,
+
+    // 敏感字段的加密密钥信息（不在配置项注册表中，只能通过 config_rekey_secrets 修改）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secrets: Option<SecretsHeader>,
 
     // 账号信息（browser_id -> AccountData）
     #[serde(default)]
     pub browser_accounts: HashMap<String, AccountData>,
 }
 
+// ========== 配置迁移 ==========
+
+// 当前配置文件结构版本
+// - v0：没有 schema_version 字段，账号数据包含 loginMethod / loginTime / lastSyncTime
+// - v1：移除账号数据中已废弃的字段
+pub const SCHEMA_VERSION: u32 = 1;
+
+// 迁移步骤：MIGRATIONS[n] 把 vn 的配置升级到 vn+1
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[migrate_v0_to_v1];
+
+// v0 → v1：移除账号数据中已废弃的动态字段
+fn migrate_v0_to_v1(doc: &mut Map<String, Value>) {
+    const DEPRECATED_ACCOUNT_FIELDS: &[&str] = &["loginMethod", "loginTime", "lastSyncTime"];
+
+    if let Some(Value::Object(accounts)) = doc.get_mut("browser_accounts") {
+        for account in accounts.values_mut() {
+            if let Value::Object(account) = account {
+                for field in DEPRECATED_ACCOUNT_FIELDS {
+                    account.remove(*field);
+                }
+            }
+        }
+    }
+}
+
+// 把配置文档逐步升级到当前版本，返回原始版本
+fn migrate(doc: &mut Value) -> Result<u32, String> {
+    let map = doc.as_object_mut().ok_or("配置文件不是 JSON 对象")?;
+    let from_version = match map.get("schema_version") {
+        None => 0,
+        Some(v) => v
+            .as_u64()
+            .map(|v| v as u32)
+            .ok_or_else(|| format!("无效的 schema_version: {}", v))?,
+    };
+
+    if from_version > SCHEMA_VERSION {
+        // 新版本程序写入的配置：不做迁移，按当前结构尽量读取，并以只读方式打开
+        println!(
+            "⚠ 配置文件版本 v{} 高于当前支持的 v{}，以只读方式打开，修改不会保存",
+            from_version, SCHEMA_VERSION
+        );
+        return Ok(from_version);
+    }
+
+    for (version, step) in MIGRATIONS.iter().enumerate().skip(from_version as usize) {
+        step(map);
+        println!("✓ 配置已从 v{} 升级到 v{}", version, version + 1);
+    }
+    map.insert("schema_version".to_string(), Value::from(SCHEMA_VERSION));
+
+    Ok(from_version)
+}
+
+// 解析配置文件内容（先升级再反序列化），返回配置和原始版本
+fn parse_config(content: &str) -> Result<(AppConfig, u32), String> {
+    let mut doc: Value = serde_json::from_str(content).map_err(|e| format!("解析失败: {}", e))?;
+    let from_version = migrate(&mut doc)?;
+    let mut config: AppConfig =
+        serde_json::from_value(doc).map_err(|e| format!("配置结构不兼容: {}", e))?;
+    // 保存时按当前结构写入
+    config.schema_version = SCHEMA_VERSION;
+    Ok((config, from_version))
+}
+
+// 按 "a.b" 形式的 key 读取嵌套字段
+fn lookup<'a>(doc: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.').try_fold(doc, |value, part| value.get(part))
+}
+
+// 按 "a.b" 形式的 key 写入嵌套字段（缺少的上级对象自动创建，null 表示删除该字段）
+fn assign(doc: &mut Value, key: &str, value: Value) {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let last = parts.pop().unwrap_or_default();
+
+    let mut current = doc;
+    for part in parts {
+        let map = match current {
+            Value::Object(map) => map,
+            _ => return,
+        };
+        let child = map.entry(part).or_insert(Value::Null);
+        if !child.is_object() {
+            *child = Value::Object(Map::new());
+        }
+        current = child;
+    }
+
+    if let Value::Object(map) = current {
+        if value.is_null() {
+            map.remove(last);
+        } else {
+            map.insert(last.to_string(), value);
+        }
+    }
+}
+
+// ========== 敏感字段加密 ==========
+
+// 所有账号的敏感字段（browser_id, 字段）
+fn sensitive_values(config: &mut AppConfig) -> impl Iterator<Item = (&String, &mut Option<String>)> {
+    config
+        .browser_accounts
+        .iter_mut()
+        .flat_map(|(browser_id, account)| account.sensitive_fields().into_iter().map(move |field| (browser_id, field)))
+}
+
+fn is_plaintext(field: &Option<String>) -> bool {
+    field.as_deref().map_or(false, |value| !config_secrets::is_encrypted(value))
+}
+
+fn is_locked(field: &Option<String>) -> bool {
+    field.as_deref().map_or(false, config_secrets::is_encrypted)
+}
+
+// 文件内容中是否有明文敏感字段（用于无法解析的文件，按文本查找）
+fn contains_plaintext_secret(content: &str) -> bool {
+    SENSITIVE_FIELD_NAMES.iter().any(|name| {
+        let quoted = format!("\"{}\"", name);
+        content.match_indices(&quoted).any(|(index, _)| {
+            let rest = content[index + quoted.len()..].trim_start();
+            let value = rest.strip_prefix(':').unwrap_or(rest).trim_start();
+            value
+                .strip_prefix('"')
+                .map_or(false, |value| !config_secrets::is_encrypted(value))
+        })
+    })
+}
+
+// 加密所有明文敏感字段（已加密的保持不变）
+fn seal_accounts(config: &mut AppConfig, key: &SecretKey) -> Result<(), String> {
+    for (_, field) in sensitive_values(config) {
+        if let Some(value) = field.as_mut().filter(|value| !config_secrets::is_encrypted(value)) {
+            *value = key.encrypt(value)?;
+        }
+    }
+    Ok(())
+}
+
+// 解密所有敏感字段，返回是否存在尚未加密的明文
+fn open_accounts(config: &mut AppConfig, key: &SecretKey) -> bool {
+    let mut plaintext_found = false;
+    for (browser_id, field) in sensitive_values(config) {
+        match field.as_mut() {
+            Some(value) if config_secrets::is_encrypted(value) => match key.decrypt(value) {
+                Ok(plaintext) => *value = plaintext,
+                Err(e) => println!("⚠ 账号 {} 的敏感数据无法解密: {}", browser_id, e),
+            },
+            Some(_) => plaintext_found = true,
+            None => {}
+        }
+    }
+    plaintext_found
+}
+
+// 未解锁时不把密文返回给前端
+fn hide_locked(mut account: AccountData) -> AccountData {
+    for field in account.sensitive_fields() {
+        if is_locked(field) {
+            *field = None;
+        }
+    }
+    account
+}
+
+const SECRETS_LOCKED_MESSAGE: &str = "敏感数据未解锁，无法保存账号的敏感数据";
+
+// 敏感数据加密状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretsStatus {
+    // 密钥来源，还没有加密过敏感数据时为空
+    pub mode: Option<KeyMode>,
+    // 敏感数据是否可用（密码模式需要先解锁）
+    pub unlocked: bool,
+}
+
+// 前端事件名称
+const CONFIG_CHANGED_EVENT: &str = "config-changed";
+const CONFIG_CONFLICT_EVENT: &str = "config-conflict";
+const CONFIG_LOAD_WARNING_EVENT: &str = "config-load-warning";
+
+// 检查配置文件外部修改的间隔（秒）
+const FILE_WATCH_INTERVAL_SECS: u64 = 2;
+
+// 配置变更事件 Payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChange {
+    // 变更的配置项（账号变更时为 browser_accounts）
+    pub key: String,
+    // 变更后的值（账号变更时不附带）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    // 是否来自配置文件的外部修改
+    pub external: bool,
+}
+
+// 外部修改与内存中的修改冲突时的警告事件 Payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigConflict {
+    // 冲突的字段路径
+    pub keys: Vec<String>,
+    pub policy: ExternalEditPolicy,
+    pub message: String,
+}
+
+// 磁盘上配置文件的已知状态（上次读取或写入时）
+struct DiskState {
+    // 上次读写时的文件内容，用作三方合并的 base
+    base: Value,
+    fingerprint: Option<(u64, SystemTime)>,
+}
+
+// 配置项订阅者，回调参数为订阅的配置项的当前值
+struct Watcher {
+    key: String,
+    callback: Arc<dyn Fn(&Value) + Send + Sync>,
+}
+
+// 合并外部修改的结果，通知在释放 disk 锁之后发送（回调中可能再次修改配置）
+#[derive(Default)]
+struct ExternalChanges {
+    // 合并结果与文件不同，需要写回文件
+    write_back: bool,
+    // 发生变化的配置项
+    keys: Vec<&'static str>,
+    // 冲突的字段
+    conflicts: Vec<String>,
+    policy: ExternalEditPolicy,
+}
+
+// 对比合并前后的内容，返回发生变化的顶层配置项（账号变更时为 browser_accounts）
+fn changed_keys(before: &Value, after: &Value) -> Vec<&'static str> {
+    let mut keys: Vec<&'static str> = config_schema::SETTINGS
+        .iter()
+        .filter(|def| !def.key.contains('.') && lookup(before, def.key) != lookup(after, def.key))
+        .map(|def| def.key)
+        .collect();
+    if lookup(before, "browser_accounts") != lookup(after, "browser_accounts") {
+        keys.push("browser_accounts");
+    }
+    keys
+}
+
+// 两个配置项是否相关（相同，或一个嵌套在另一个之下）
+fn keys_overlap(a: &str, b: &str) -> bool {
+    let nested = |outer: &str, inner: &str| {
+        inner.len() > outer.len() && inner.starts_with(outer) && inner.as_bytes()[outer.len()] == b'.'
+    };
+    a == b || nested(a, b) || nested(b, a)
+}
+
 // 全局配置实例
 pub struct ConfigManager {
     config: Mutex<AppConfig>,
+    // 启动时读取配置文件的结果（是否从备份恢复）
+    load_report: LoadReport,
+    // 配置变更订阅者
+    watchers: Mutex<Vec<Watcher>>,
+    // 用于推送 config-changed 事件（setup 中设置）
+    app_handle: Mutex<Option<tauri::AppHandle>>,
+    // 磁盘状态，同时用于串行化“检查外部修改 + 写入”
+    disk: Mutex<DiskState>,
+    // 敏感字段的密钥，None 表示尚未解锁（内存中的敏感字段保持密文）
+    secret_key: Mutex<Option<SecretKey>>,
+    // 配置目录（settings.json 和密钥文件所在目录）
+    dir: PathBuf,
+    // 配置文件由更新版本的程序写入时的版本号，此时不保存修改
+    newer_version: Mutex<Option<u32>>,
 }
 
 impl ConfigManager {
     pub fn new() -> Self {
-        let config = Self::load_config();
-        ConfigManager {
+        Self::open(get_config_dir())
+    }
+
+    fn open(dir: PathBuf) -> Self {
+        let config_path = dir.join(CONFIG_FILE_NAME);
+        let (mut config, load_report) = Self::load_config(&config_path);
+        let (secret_key, plaintext_found) = Self::init_secrets(&mut config, &dir);
+        let disk = DiskState {
+            base: serde_json::to_value(&config).unwrap_or(Value::Null),
+            fingerprint: config_store::fingerprint(&config_path),
+        };
+        let newer_version = load_report.newer_version;
+        let manager = ConfigManager {
             config: Mutex::new(config),
+            load_report,
+            watchers: Mutex::new(Vec::new()),
+            app_handle: Mutex::new(None),
+            disk: Mutex::new(disk),
+            secret_key: Mutex::new(secret_key),
+            dir,
+            newer_version: Mutex::new(newer_version),
+        };
+
+        if plaintext_found {
+            // 旧版本保存的明文敏感数据，立即加密写回
+            if let Err(e) = manager.save_config() {
+                println!("⚠ 加密敏感数据失败: {}", e);
+            }
         }
+        manager
     }
 
-    // 加载配置文件
-    fn load_config() -> AppConfig {
-        let config_path = get_config_path();
+    // 启动时恢复密钥并解密敏感字段，返回密钥和是否存在明文敏感数据
+    //
+    // 密钥文件模式自动解锁；密码模式等待 config_unlock_secrets
+    fn init_secrets(config: &mut AppConfig, dir: &Path) -> (Option<SecretKey>, bool) {
+        let header = match config.secrets.clone() {
+            Some(header) => header,
+            None => return (None, sensitive_values(config).any(|(_, field)| is_plaintext(field))),
+        };
+        if header.mode == KeyMode::Passphrase {
+            println!("⚠ 敏感数据已使用密码加密，解锁前账号的 linkToken 不可用");
+            return (None, false);
+        }
 
-        if config_path.exists() {
-            if let Ok(content) = fs::read_to_string(&config_path) {
-                if let Ok(config) = serde_json::from_str::<AppConfig>(&content) {
-                    return config;
-                }
+        match config_secrets::open(&header, None, dir) {
+            Ok(key) => {
+                let plaintext_found = open_accounts(config, &key);
+                (Some(key), plaintext_found)
+            }
+            Err(e) => {
+                println!("⚠ 无法解锁敏感数据: {}", e);
+                (None, false)
+            }
+        }
+    }
+
+    // ========== 变更订阅 ==========
+
+    // 设置 AppHandle 后，配置变更会推送 config-changed 事件到前端
+    pub fn attach_app_handle(&self, app_handle: tauri::AppHandle) {
+        *self.app_handle.lock().unwrap() = Some(app_handle);
+    }
+
+    // 订阅配置项变更（注册时立即以当前值调用一次）
+    //
+    // 配置项本身、其上级对象或其下级字段变更时都会触发回调
+    pub fn subscribe(
+        &self,
+        key: &str,
+        callback: impl Fn(&Value) + Send + Sync + 'static,
+    ) -> Result<(), String> {
+        callback(&self.get_value(key)?);
+        self.watchers.lock().unwrap().push(Watcher {
+            key: key.to_string(),
+            callback: Arc::new(callback),
+        });
+        Ok(())
+    }
+
+    // 通知订阅者和前端
+    //
+    // 回调在 watchers 锁之外调用，回调中可以订阅或修改配置
+    fn notify(&self, key: &str, value: Option<Value>, external: bool) {
+        let watchers: Vec<_> = self
+            .watchers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|watcher| keys_overlap(&watcher.key, key))
+            .map(|watcher| (watcher.key.clone(), watcher.callback.clone()))
+            .collect();
+        for (watched, callback) in watchers {
+            match self.get_value(&watched) {
+                Ok(current) => callback(&current),
+                Err(e) => println!("⚠ 读取配置项 {} 失败: {}", watched, e),
+            }
+        }
+
+        if let Some(app_handle) = self.app_handle.lock().unwrap().as_ref() {
+            let change = ConfigChange {
+                key: key.to_string(),
+                value,
+                external,
+            };
+            if let Err(e) = app_handle.emit_all(CONFIG_CHANGED_EVENT, &change) {
+                eprintln!("⚠ 推送配置变更事件失败: {}", e);
             }
         }
+    }
+
+    fn config_path(&self) -> PathBuf {
+        self.dir.join(CONFIG_FILE_NAME)
+    }
+
+    // 加载配置文件（损坏时从备份恢复，旧版本逐步升级）
+    fn load_config(config_path: &Path) -> (AppConfig, LoadReport) {
+        let (loaded, mut report) = config_store::load(config_path, parse_config);
+
+        let config = match loaded {
+            Some((config, from_version)) => {
+                if from_version > SCHEMA_VERSION {
+                    report.newer_version = Some(from_version);
+                }
+                if from_version < SCHEMA_VERSION {
+                    report.migrated_from = Some(from_version);
+                    // 写回升级后的配置（旧文件会轮换到备份中）
+                    if let Err(e) = config_store::save(config_path, &config) {
+                        println!("⚠ 保存升级后的配置失败: {}", e);
+                    }
+                }
+                config
+            }
+            None => AppConfig {
+                schema_version: SCHEMA_VERSION,
+                ..Default::default()
+            },
+        };
+
+        report.warning = report.warning_message();
+        (config, report)
+    }
 
-        AppConfig::default()
+    // 更新版本的程序写入的配置文件只读：按当前结构写回会丢掉新版本的字段
+    //
+    // 修改内存中的配置之前检查，避免内存和文件不一致
+    fn ensure_writable(&self) -> Result<(), String> {
+        match *self.newer_version.lock().unwrap() {
+            Some(version) => Err(format!(
+                "配置文件由更新版本的程序写入（v{}，当前支持 v{}），为避免丢失数据不会保存修改",
+                version, SCHEMA_VERSION
+            )),
+            None => Ok(()),
+        }
     }
 
-    // 保存配置文件
+    // 保存配置文件（原子写入并轮换备份）
     fn save_config(&self) -> Result<(), String> {
-        let config = self.config.lock().unwrap();
-        let config_path = get_config_path();
+        let mut disk = self.disk.lock().unwrap();
+        // 先合并尚未发现的外部修改，避免覆盖
+        let changes = self.reconcile_external(&mut disk);
+        let result = self.write_config(&mut disk);
+        drop(disk);
 
-        let json =
-            serde_json::to_string_pretty(&*config).map_err(|e| format!("序列化配置失败: {}", e))?;
+        self.notify_external(changes);
+        result
+    }
 
-        fs::write(&config_path, json).map_err(|e| format!("写入配置文件失败: {}", e))?;
+    fn write_config(&self, disk: &mut DiskState) -> Result<(), String> {
+        self.ensure_writable()?;
+        let mut config = self.config.lock().unwrap();
+        let sealed = self.seal_for_disk(&mut config)?;
+        let config_path = self.config_path();
+        // 被替换的文件含明文敏感数据时（首次加密、旧版本升级、外部写入），它会被轮换到备份中
+        let replaced_plaintext =
+            fs::read_to_string(&config_path).map_or(false, |content| contains_plaintext_secret(&content));
+        config_store::save(&config_path, &sealed)?;
 
+        disk.base = serde_json::to_value(&*config).unwrap_or(Value::Null);
+        disk.fingerprint = config_store::fingerprint(&config_path);
+        drop(config);
+
+        if replaced_plaintext {
+            self.secure_backups(None);
+        }
         Ok(())
     }
 
-    // ========== 基础配置方法 ==========
+    // 处理备份和损坏副本中的敏感数据：
+    // - 备份中的明文或旧密钥加密的数据用当前密钥重新加密（旧密钥无法恢复时保持原样）
+    // - 含明文敏感数据的损坏副本无法重新加密，直接删除
+    // - 删除不再被配置文件和备份引用的密钥文件
+    //
+    // previous 为更换前的密钥（密码模式的旧密钥无法从密钥文件恢复）
+    fn secure_backups(&self, previous: Option<(&SecretsHeader, &SecretKey)>) {
+        let header = self.config.lock().unwrap().secrets.clone();
+        let key = self.secret_key.lock().unwrap().clone();
+        let (header, key) = match (header, key) {
+            (Some(header), Some(key)) => (header, key),
+            _ => return,
+        };
 
-    pub fn get_string(&self, key: &str) -> Option<String> {
-        let config = self.config.lock().unwrap();
-        match key {
-            "bitbrowser_path" => config.bitbrowser_path.clone(),
-            "bitbrowser_api" => config.bitbrowser_api.clone(),
-            "username" => config.username.clone(),
-            _ => None,
+        let config_path = self.config_path();
+        let mut used_key_ids = vec![header.key_id.clone()];
+        for backup in config_store::backup_paths(&config_path) {
+            let content = match fs::read_to_string(&backup) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            let mut config = match parse_config(&content) {
+                Ok((config, _)) => config,
+                Err(_) => {
+                    // 无效的备份不会用于恢复
+                    if contains_plaintext_secret(&content) {
+                        let _ = fs::remove_file(&backup);
+                    }
+                    continue;
+                }
+            };
+
+            match self.reseal(&mut config, &header, &key, previous) {
+                Ok(false) => {}
+                Ok(true) => {
+                    let written = serde_json::to_string_pretty(&config)
+                        .map_err(|e| e.to_string())
+                        .and_then(|json| config_store::write_atomic(&backup, json.as_bytes()));
+                    if let Err(e) = written {
+                        println!("⚠ 重新加密备份 {} 失败，已删除: {}", backup.display(), e);
+                        let _ = fs::remove_file(&backup);
+                    }
+                }
+                Err(e) => {
+                    println!("⚠ 备份 {} 中的敏感数据无法重新加密: {}", backup.display(), e);
+                    if let Some(old) = &config.secrets {
+                        used_key_ids.push(old.key_id.clone());
+                    }
+                }
+            }
+        }
+
+        for copy in config_store::corrupt_copies(&config_path) {
+            if fs::read_to_string(&copy).map_or(false, |content| contains_plaintext_secret(&content)) {
+                println!("⚠ 已删除含明文敏感数据的损坏副本: {}", copy.display());
+                let _ = fs::remove_file(&copy);
+            }
         }
+
+        config_secrets::remove_unused_key_files(&self.dir, &used_key_ids);
     }
 
-    pub fn set_string(&self, key: &str, value: String) -> Result<(), String> {
+    // 用当前密钥重新加密一份备份，返回是否有修改
+    fn reseal(
+        &self,
+        config: &mut AppConfig,
+        header: &SecretsHeader,
+        key: &SecretKey,
+        previous: Option<(&SecretsHeader, &SecretKey)>,
+    ) -> Result<bool, String> {
+        let old_key = match &config.secrets {
+            // 尚未加密过的备份
+            None => None,
+            Some(old) if old.key_id == header.key_id => {
+                if !sensitive_values(config).any(|(_, field)| is_plaintext(field)) {
+                    return Ok(false);
+                }
+                Some(key.clone())
+            }
+            Some(old) => match previous.filter(|(previous, _)| previous.key_id == old.key_id) {
+                Some((_, previous_key)) => Some(previous_key.clone()),
+                None => Some(config_secrets::open(old, None, &self.dir)?),
+            },
+        };
+
+        if let Some(old_key) = old_key {
+            open_accounts(config, &old_key);
+        }
+        seal_accounts(config, key)?;
+        config.secrets = Some(header.clone());
+        Ok(true)
+    }
+
+    // 生成写入磁盘的配置：敏感字段加密，内存中的配置保持明文
+    fn seal_for_disk(&self, config: &mut AppConfig) -> Result<AppConfig, String> {
+        let mut sealed = config.clone();
+        if !sensitive_values(&mut sealed).any(|(_, field)| is_plaintext(field)) {
+            return Ok(sealed);
+        }
+
+        let mut secret_key = self.secret_key.lock().unwrap();
+        if secret_key.is_none() {
+            if config.secrets.is_some() {
+                return Err(SECRETS_LOCKED_MESSAGE.to_string());
+            }
+            // 首次保存敏感数据时生成本机密钥文件
+            let (header, key) = config_secrets::generate(KeyMode::KeyFile, None, &self.dir)?;
+            println!("✓ 已生成本机密钥文件，敏感数据将加密保存");
+            config.secrets = Some(header.clone());
+            sealed.secrets = Some(header);
+            *secret_key = Some(key);
+        }
+
+        if let Some(key) = secret_key.as_ref() {
+            seal_accounts(&mut sealed, key)?;
+        }
+        Ok(sealed)
+    }
+
+    // ========== 外部修改检测 ==========
+
+    // 检查配置文件是否被外部修改，有修改时按策略合并或重新加载
+    pub fn check_external_changes(&self) {
+        let mut disk = self.disk.lock().unwrap();
+        let changes = self.reconcile_external(&mut disk);
+        if changes.write_back {
+            // 合并后内存中的内容与文件不同（保留了内存中的修改），写回文件
+            if let Err(e) = self.write_config(&mut disk) {
+                println!("⚠ 写回合并后的配置失败: {}", e);
+            }
+        }
+        drop(disk);
+
+        self.notify_external(changes);
+    }
+
+    // 把外部修改合并到内存中，返回需要发送的通知和合并结果是否需要写回文件
+    //
+    // 调用方持有 disk 锁，释放后再调用 notify_external
+    fn reconcile_external(&self, disk: &mut DiskState) -> ExternalChanges {
+        let config_path = self.config_path();
+        let fingerprint = config_store::fingerprint(&config_path);
+        if fingerprint == disk.fingerprint {
+            return ExternalChanges::default();
+        }
+        disk.fingerprint = fingerprint;
+
+        // 文件被删除时下次保存会重新创建
+        let content = match fs::read_to_string(&config_path) {
+            Ok(content) => content,
+            Err(_) => return ExternalChanges::default(),
+        };
+        let mut plaintext_found = false;
+        let theirs = match parse_config(&content) {
+            Ok((mut config, from_version)) => {
+                *self.newer_version.lock().unwrap() = Some(from_version).filter(|v| *v > SCHEMA_VERSION);
+                // 解密后再合并，base / ours 中的敏感字段都是明文
+                if let Some(key) = self.secret_key.lock().unwrap().as_ref() {
+                    plaintext_found = open_accounts(&mut config, key);
+                }
+                serde_json::to_value(config).unwrap_or(Value::Null)
+            }
+            Err(e) => {
+                println!("⚠ 配置文件被外部修改为无效内容，已忽略: {}", e);
+                return ExternalChanges::default();
+            }
+        };
+
+        let (ours, policy) = {
+            let config = self.config.lock().unwrap();
+            (
+                serde_json::to_value(&*config).unwrap_or(Value::Null),
+                config.external_edit_policy.unwrap_or_default(),
+            )
+        };
+
+        let mut conflicts = Vec::new();
+        let merged = config_store::merge_documents(Some(&disk.base), Some(&ours), Some(&theirs), "", &mut conflicts)
+            .unwrap_or(Value::Null);
+        let merged = match policy {
+            ExternalEditPolicy::Merge => merged,
+            ExternalEditPolicy::Reload => theirs.clone(),
+        };
+
+        match serde_json::from_value::<AppConfig>(merged.clone()) {
+            Ok(config) => *self.config.lock().unwrap() = config,
+            Err(e) => {
+                println!("⚠ 合并外部修改失败，保留内存中的配置: {}", e);
+                disk.base = theirs;
+                return ExternalChanges {
+                    write_back: true,
+                    ..Default::default()
+                };
+            }
+        }
+        disk.base = theirs.clone();
+        println!("✓ 检测到配置文件被外部修改，已{}", match policy {
+            ExternalEditPolicy::Merge => "合并",
+            ExternalEditPolicy::Reload => "重新加载",
+        });
+
+        ExternalChanges {
+            // 文件中出现明文敏感数据时也需要写回加密
+            write_back: merged != theirs || plaintext_found,
+            keys: changed_keys(&ours, &merged),
+            conflicts,
+            policy,
+        }
+    }
+
+    // 发送外部修改的冲突警告和变更通知（不能持有 disk 锁）
+    fn notify_external(&self, changes: ExternalChanges) {
+        if !changes.conflicts.is_empty() {
+            self.warn_conflict(changes.conflicts, changes.policy);
+        }
+        for key in changes.keys {
+            let value = if key == "browser_accounts" { None } else { self.get_value(key).ok() };
+            self.notify(key, value, true);
+        }
+    }
+
+    fn warn_conflict(&self, keys: Vec<String>, policy: ExternalEditPolicy) {
+        let message = match policy {
+            ExternalEditPolicy::Merge => format!(
+                "配置文件的外部修改与程序中的修改冲突，已保留程序中的值: {}",
+                keys.join(", ")
+            ),
+            ExternalEditPolicy::Reload => format!(
+                "配置文件的外部修改与程序中的修改冲突，已使用文件中的值: {}",
+                keys.join(", ")
+            ),
+        };
+        println!("⚠ {}", message);
+
+        if let Some(app_handle) = self.app_handle.lock().unwrap().as_ref() {
+            let conflict = ConfigConflict { keys, policy, message };
+            if let Err(e) = app_handle.emit_all(CONFIG_CONFLICT_EVENT, &conflict) {
+                eprintln!("⚠ 推送配置冲突事件失败: {}", e);
+            }
+        }
+    }
+
+    pub fn load_report(&self) -> LoadReport {
+        self.load_report.clone()
+    }
+
+    // 读取配置时从备份恢复、使用了默认配置或配置只读时，向前端推送警告
+    pub fn emit_load_warning(&self) {
+        if self.load_report.warning.is_none() {
+            return;
+        }
+        if let Some(app_handle) = self.app_handle.lock().unwrap().as_ref() {
+            if let Err(e) = app_handle.emit_all(CONFIG_LOAD_WARNING_EVENT, &self.load_report) {
+                eprintln!("⚠ 推送配置读取警告失败: {}", e);
+            }
+        }
+    }
+
+    // ========== 通用配置读写（按 config_schema 注册表校验） ==========
+
+    // 读取配置项，未设置时返回注册表中的默认值
+    pub fn get_value(&self, key: &str) -> Result<Value, String> {
+        let def = config_schema::find(key)?;
+        let config = self.config.lock().unwrap();
+        let doc = serde_json::to_value(&*config).map_err(|e| format!("序列化配置失败: {}", e))?;
+
+        Ok(lookup(&doc, key)
+            .filter(|v| !v.is_null())
+            .cloned()
+            .unwrap_or_else(|| def.default_value()))
+    }
+
+    // 设置配置项，null 表示恢复默认值
+    pub fn set_value(&self, key: &str, value: Value) -> Result<(), String> {
+        config_schema::find(key)?.check(&value)?;
+        self.ensure_writable()?;
+
         let mut config = self.config.lock().unwrap();
-        match key {
-            "bitbrowser_path" => config.bitbrowser_path = Some(value),
-            "bitbrowser_api" => config.bitbrowser_api = Some(value),
-            "username" => config.username = Some(value),
-            _ => return Err(format!("未知的配置项: {}", key)),
+        let mut doc = serde_json::to_value(&*config).map_err(|e| format!("序列化配置失败: {}", e))?;
+        let previous = lookup(&doc, key).filter(|v| !v.is_null()).cloned();
+        if previous.as_ref() == Some(&value).filter(|v| !v.is_null()) {
+            return Ok(());
         }
+        assign(&mut doc, key, value);
+
+        // 嵌套字段变更后重新校验所在的对象
+        for (index, _) in key.match_indices('.') {
+            let parent = &key[..index];
+            if let (Ok(def), Some(parent_value)) = (config_schema::find(parent), lookup(&doc, parent)) {
+                def.check(parent_value)?;
+            }
+        }
+
+        *config = serde_json::from_value(doc).map_err(|e| format!("配置项 {} 的值无效: {}", key, e))?;
         drop(config);
-        self.save_config()
+        self.save_config()?;
+
+        self.notify(key, self.get_value(key).ok(), false);
+        Ok(())
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Result<T, String> {
+        serde_json::from_value(self.get_value(key)?).map_err(|e| format!("配置项 {} 的类型不匹配: {}", key, e))
+    }
+
+    pub fn set<T: Serialize>(&self, key: &str, value: T) -> Result<(), String> {
+        let value = serde_json::to_value(value).map_err(|e| format!("序列化配置项 {} 失败: {}", key, e))?;
+        self.set_value(key, value)
+    }
+
+    // ========== 基础配置方法 ==========
+
+    pub fn get_string(&self, key: &str) -> Option<String> {
+        self.get::<Option<String>>(key).ok().flatten()
+    }
+
+    pub fn set_string(&self, key: &str, value: String) -> Result<(), String> {
+        self.set(key, value)
     }
 
     pub fn get_bool(&self, key: &str) -> Option<bool> {
-        let config = self.config.lock().unwrap();
-        match key {
-            "filter_my_accounts" => config.filter_my_accounts,
-            "member_mode" => config.member_mode,
-            "test_persistence_mode" => config.test_persistence_mode,
-            "bitbrowser_vip_mode" => config.bitbrowser_vip_mode,
-            _ => None,
-        }
+        self.get::<bool>(key).ok()
     }
 
     pub fn set_bool(&self, key: &str, value: bool) -> Result<(), String> {
-        let mut config = self.config.lock().unwrap();
-        match key {
-            "filter_my_accounts" => config.filter_my_accounts = Some(value),
-            "member_mode" => config.member_mode = Some(value),
-            "test_persistence_mode" => config.test_persistence_mode = Some(value),
-            "bitbrowser_vip_mode" => config.bitbrowser_vip_mode = Some(value),
-            _ => return Err(format!("未知的配置项: {}", key)),
+        self.set(key, value)
+    }
+
+    // ========== BitBrowser 启动配置 ==========
+
+    pub fn get_launch_profile(&self) -> Option<LaunchProfile> {
+        self.get::<Option<LaunchProfile>>("bitbrowser_launch").ok().flatten()
+    }
+
+    pub fn set_launch_profile(&self, profile: Option<LaunchProfile>) -> Result<(), String> {
+        self.set("bitbrowser_launch", profile)
+    }
+
+    // ========== 敏感数据密钥 ==========
+
+    pub fn secrets_status(&self) -> SecretsStatus {
+        let mode = self.config.lock().unwrap().secrets.as_ref().map(|header| header.mode);
+        SecretsStatus {
+            mode,
+            unlocked: mode.is_none() || self.secret_key.lock().unwrap().is_some(),
         }
-        drop(config);
-        self.save_config()
+    }
+
+    // 用密码解锁敏感数据（密钥文件模式下重新读取密钥文件）
+    pub fn unlock_secrets(&self, passphrase: &str) -> Result<(), String> {
+        let mut disk = self.disk.lock().unwrap();
+        if self.secret_key.lock().unwrap().is_some() {
+            return Ok(());
+        }
+        let header = self.config.lock().unwrap().secrets.clone().ok_or("没有需要解锁的敏感数据")?;
+        let key = config_secrets::open(&header, Some(passphrase), &self.dir)?;
+
+        let plaintext_found = open_accounts(&mut self.config.lock().unwrap(), &key);
+        // 文件内容没有变化，base 同样换成明文
+        if let Ok(mut base) = serde_json::from_value::<AppConfig>(disk.base.clone()) {
+            open_accounts(&mut base, &key);
+            disk.base = serde_json::to_value(base).unwrap_or(Value::Null);
+        }
+        *self.secret_key.lock().unwrap() = Some(key);
+        println!("✓ 敏感数据已解锁");
+
+        if plaintext_found {
+            if let Err(e) = self.write_config(&mut disk) {
+                println!("⚠ 加密敏感数据失败: {}", e);
+            }
+        }
+        drop(disk);
+        self.notify("browser_accounts", None, false);
+        Ok(())
+    }
+
+    // 更换密钥并重新加密所有敏感数据
+    //
+    // 未解锁时需要 discard_locked 才能继续，无法解密的数据会被丢弃
+    // （用于密码遗忘或密钥文件丢失的情况）
+    pub fn rekey_secrets(
+        &self,
+        mode: KeyMode,
+        passphrase: Option<&str>,
+        discard_locked: bool,
+    ) -> Result<(), String> {
+        self.ensure_writable()?;
+        let mut disk = self.disk.lock().unwrap();
+        let changes = self.reconcile_external(&mut disk);
+        let result = self.rekey_locked(&mut disk, mode, passphrase, discard_locked);
+        drop(disk);
+        self.notify_external(changes);
+        let discarded = result?;
+
+        println!(
+            "✓ 敏感数据已使用新的{}重新加密",
+            match mode {
+                KeyMode::Passphrase => "密码",
+                KeyMode::KeyFile => "本机密钥文件",
+            }
+        );
+        if discarded > 0 {
+            println!("⚠ 已丢弃 {} 项无法解密的敏感数据", discarded);
+            self.notify("browser_accounts", None, false);
+        }
+        Ok(())
+    }
+
+    // rekey_secrets 的实现（持有 disk 锁），返回丢弃的敏感数据数量
+    fn rekey_locked(
+        &self,
+        disk: &mut DiskState,
+        mode: KeyMode,
+        passphrase: Option<&str>,
+        discard_locked: bool,
+    ) -> Result<usize, String> {
+        let old_key = self.secret_key.lock().unwrap().clone();
+        let previous = self.config.lock().unwrap().clone();
+        if old_key.is_none() && previous.secrets.is_some() && !discard_locked {
+            return Err("敏感数据未解锁，请先解锁再更换密钥".to_string());
+        }
+        let (header, key) = config_secrets::generate(mode, passphrase, &self.dir)?;
+
+        let mut discarded = 0;
+        {
+            let mut config = self.config.lock().unwrap();
+            for (_, field) in sensitive_values(&mut config) {
+                if is_locked(field) {
+                    *field = None;
+                    discarded += 1;
+                }
+            }
+            config.secrets = Some(header.clone());
+        }
+        *self.secret_key.lock().unwrap() = Some(key);
+
+        if let Err(e) = self.write_config(disk) {
+            *self.config.lock().unwrap() = previous;
+            *self.secret_key.lock().unwrap() = old_key;
+            config_secrets::remove_key_file(&header, &self.dir);
+            return Err(e);
+        }
+        // 备份改用新密钥加密，旧密钥文件在没有备份引用后删除
+        let old = previous.secrets.as_ref().zip(old_key.as_ref());
+        self.secure_backups(old);
+        Ok(discarded)
     }
 
     // ========== 账号信息管理 ==========
 
     pub fn get_all_accounts(&self) -> HashMap<String, AccountData> {
         let config = self.config.lock().unwrap();
-        config.browser_accounts.clone()
+        config
+            .browser_accounts
+            .iter()
+            .map(|(browser_id, account)| (browser_id.clone(), hide_locked(account.clone())))
+            .collect()
     }
 
     pub fn get_account(&self, browser_id: &str) -> Option<AccountData> {
         let config = self.config.lock().unwrap();
-        config.browser_accounts.get(browser_id).cloned()
+        config.browser_accounts.get(browser_id).cloned().map(hide_locked)
     }
 
     pub fn save_account(
         &self,
         browser_id: String,
-        account_data: AccountData,
+        mut account_data: AccountData,
     ) -> Result<(), String> {
+        self.ensure_writable()?;
         let mut config = self.config.lock().unwrap();
+        // 未解锁时不能加密新的敏感数据，在修改内存中的配置之前拒绝
+        // （否则明文留在内存中，之后的每次保存都会失败）
+        let locked = config.secrets.is_some() && self.secret_key.lock().unwrap().is_none();
+        if locked && account_data.sensitive_fields().into_iter().any(|field| is_plaintext(field)) {
+            return Err(SECRETS_LOCKED_MESSAGE.to_string());
+        }
+
+        // 未解锁时前端读到的敏感字段为空，保留原有的密文
+        if let Some(existing) = config.browser_accounts.get_mut(&browser_id) {
+            for (field, existing) in account_data.sensitive_fields().into_iter().zip(existing.sensitive_fields()) {
+                if field.is_none() && is_locked(existing) {
+                    *field = existing.take();
+                }
+            }
+        }
         config.browser_accounts.insert(browser_id, account_data);
         drop(config);
-        self.save_config()
+        self.save_config()?;
+        self.notify("browser_accounts", None, false);
+        Ok(())
     }
 
     pub fn delete_account(&self, browser_id: &str) -> Result<(), String> {
+        self.ensure_writable()?;
         let mut config = self.config.lock().unwrap();
         config.browser_accounts.remove(browser_id);
         drop(config);
-        self.save_config()
+        self.save_config()?;
+        self.notify("browser_accounts", None, false);
+        Ok(())
     }
 
     pub fn delete_accounts(&self, browser_ids: Vec<String>) -> Result<(), String> {
+        self.ensure_writable()?;
         let mut config = self.config.lock().unwrap();
         for browser_id in browser_ids {
             config.browser_accounts.remove(&browser_id);
         }
         drop(config);
-        self.save_config()
+        self.save_config()?;
+        self.notify("browser_accounts", None, false);
+        Ok(())
     }
 }
 
+// 启动后台任务，定期检查配置文件的外部修改
+pub fn start_file_watcher(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(FILE_WATCH_INTERVAL_SECS));
+        loop {
+            ticker.tick().await;
+            app_handle.state::<ConfigManager>().check_external_changes();
+        }
+    });
+}
+
 // ========== Tauri 命令 ==========
 
 #[tauri::command]
@@ -237,6 +1142,39 @@ pub fn config_set_bool(
     state.set_bool(&key, value)
 }
 
+#[tauri::command]
+pub fn config_get(key: String, state: tauri::State<ConfigManager>) -> Result<Value, String> {
+    state.get_value(&key)
+}
+
+#[tauri::command]
+pub fn config_set(key: String, value: Value, state: tauri::State<ConfigManager>) -> Result<(), String> {
+    state.set_value(&key, value)
+}
+
+#[tauri::command]
+pub fn config_list_schema() -> Vec<SettingDef> {
+    config_schema::SETTINGS.to_vec()
+}
+
+#[tauri::command]
+pub fn config_get_load_report(state: tauri::State<ConfigManager>) -> LoadReport {
+    state.load_report()
+}
+
+#[tauri::command]
+pub fn config_get_launch_profile(state: tauri::State<ConfigManager>) -> LaunchProfile {
+    state.get_launch_profile().unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn config_set_launch_profile(
+    profile: Option<LaunchProfile>,
+    state: tauri::State<ConfigManager>,
+) -> Result<(), String> {
+    state.set_launch_profile(profile)
+}
+
 #[tauri::command]
 pub fn config_get_all_accounts(state: tauri::State<ConfigManager>) -> HashMap<String, AccountData> {
     state.get_all_accounts()
@@ -274,3 +1212,273 @@ pub fn config_delete_accounts(
 ) -> Result<(), String> {
     state.delete_accounts(browser_ids)
 }
+
+#[tauri::command]
+pub fn config_get_secrets_status(state: tauri::State<ConfigManager>) -> SecretsStatus {
+    state.secrets_status()
+}
+
+#[tauri::command]
+pub fn config_unlock_secrets(passphrase: String, state: tauri::State<ConfigManager>) -> Result<(), String> {
+    state.unlock_secrets(&passphrase)
+}
+
+#[tauri::command]
+pub fn config_rekey_secrets(
+    mode: KeyMode,
+    passphrase: Option<String>,
+    discard_locked: Option<bool>,
+    state: tauri::State<ConfigManager>,
+) -> Result<(), String> {
+    state.rekey_secrets(mode, passphrase.as_deref(), discard_locked.unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V0_FIXTURE: &str = include_str!("../fixtures/settings/v0.json");
+    const V1_FIXTURE: &str = include_str!("../fixtures/settings/v1.json");
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("config-manager-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // 写入一份用密码加密过敏感数据的配置
+    fn write_passphrase_config(dir: &Path) {
+        let (mut config, _) = parse_config(V1_FIXTURE).unwrap();
+        let (header, key) = config_secrets::generate(KeyMode::Passphrase, Some("correct horse"), dir).unwrap();
+        seal_accounts(&mut config, &key).unwrap();
+        config.secrets = Some(header);
+        config_store::save(&dir.join(CONFIG_FILE_NAME), &config).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_v0_fixture() {
+        let (config, from_version) = parse_config(V0_FIXTURE).unwrap();
+        assert_eq!(from_version, 0);
+        assert_eq!(config.schema_version, SCHEMA_VERSION);
+        assert_eq!(config.username.as_deref(), Some("operator"));
+        assert_eq!(config.filter_my_accounts, Some(true));
+        assert_eq!(config.browser_accounts.len(), 2);
+
+        let account = &config.browser_accounts["2c9c29a2a6f9"];
+        assert_eq!(account.link_token.as_deref(), Some("lt_8f1e2d"));
+        assert_eq!(
+            account.account_info.finder_username.as_deref(),
+            Some("v2_060000231003b20faec8c")
+        );
+
+        // 升级后的文档不再包含废弃字段
+        let saved = serde_json::to_value(&config).unwrap();
+        assert_eq!(saved["schema_version"], SCHEMA_VERSION);
+        let saved_account = saved["browser_accounts"]["2c9c29a2a6f9"].as_object().unwrap();
+        for field in ["loginMethod", "loginTime", "lastSyncTime"] {
+            assert!(!saved_account.contains_key(field), "{} 未移除", field);
+        }
+    }
+
+    #[test]
+    fn test_current_fixture_is_unchanged() {
+        let (config, from_version) = parse_config(V1_FIXTURE).unwrap();
+        assert_eq!(from_version, SCHEMA_VERSION);
+        assert_eq!(config.bitbrowser_api_token.as_deref(), Some("token-123"));
+        assert!(config.bitbrowser_launch.as_ref().unwrap().detached);
+
+        let original: Value = serde_json::from_str(V1_FIXTURE).unwrap();
+        assert_eq!(serde_json::to_value(&config).unwrap(), original);
+    }
+
+    #[test]
+    fn test_seal_and_open_accounts() {
+        let (mut config, _) = parse_config(V1_FIXTURE).unwrap();
+        let (_, key) =
+            config_secrets::generate(KeyMode::Passphrase, Some("correct horse"), &std::env::temp_dir()).unwrap();
+
+        seal_accounts(&mut config, &key).unwrap();
+        let sealed = serde_json::to_string(&config).unwrap();
+        assert!(!sealed.contains("lt_8f1e2d"));
+        // 已加密的字段不会重复加密
+        let account = config.browser_accounts["2c9c29a2a6f9"].clone();
+        seal_accounts(&mut config, &key).unwrap();
+        assert_eq!(config.browser_accounts["2c9c29a2a6f9"].link_token, account.link_token);
+
+        // 未解锁时不返回密文
+        assert_eq!(hide_locked(account).link_token, None);
+
+        assert!(!open_accounts(&mut config, &key));
+        assert_eq!(
+            config.browser_accounts["2c9c29a2a6f9"].link_token.as_deref(),
+            Some("lt_8f1e2d")
+        );
+        // 明文字段视为尚未加密
+        assert!(open_accounts(&mut config, &key));
+    }
+
+    #[test]
+    fn test_locked_save_does_not_block_other_saves() {
+        let dir = temp_dir("locked-save");
+        write_passphrase_config(&dir);
+        let manager = ConfigManager::open(dir.clone());
+        assert!(!manager.secrets_status().unlocked);
+
+        let mut account = manager.get_account("2c9c29a2a6f9").unwrap();
+        assert_eq!(account.link_token, None);
+        account.link_token = Some("lt_new".to_string());
+        assert!(manager.save_account("2c9c29a2a6f9".to_string(), account).is_err());
+
+        // 被拒绝的明文没有留在内存中，其他配置仍然可以保存
+        manager.set_bool("member_mode", true).unwrap();
+        let saved = fs::read_to_string(dir.join(CONFIG_FILE_NAME)).unwrap();
+        assert!(!saved.contains("lt_new") && !saved.contains("lt_8f1e2d"));
+
+        manager.unlock_secrets("correct horse").unwrap();
+        let account = manager.get_account("2c9c29a2a6f9").unwrap();
+        assert_eq!(account.link_token.as_deref(), Some("lt_8f1e2d"));
+    }
+
+    #[test]
+    fn test_backups_are_encrypted_and_rekeyed() {
+        let dir = temp_dir("backups");
+        let config_path = dir.join(CONFIG_FILE_NAME);
+        // 旧版本留下的明文配置、明文备份和损坏副本
+        fs::write(&config_path, V1_FIXTURE).unwrap();
+        fs::write(dir.join("settings.json.bak.1"), V0_FIXTURE).unwrap();
+        fs::write(dir.join("settings.json.corrupt-1"), format!("{},", V1_FIXTURE)).unwrap();
+        assert!(contains_plaintext_secret(&fs::read_to_string(dir.join("settings.json.corrupt-1")).unwrap()));
+
+        let files_with = |text: &str| -> Vec<PathBuf> {
+            fs::read_dir(&dir)
+                .unwrap()
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| fs::read_to_string(path).map_or(false, |content| content.contains(text)))
+                .collect()
+        };
+        let key_files = || -> usize {
+            fs::read_dir(&dir)
+                .unwrap()
+                .flatten()
+                .filter(|entry| entry.file_name().to_string_lossy().ends_with(".key"))
+                .count()
+        };
+
+        let manager = ConfigManager::open(dir.clone());
+        assert_eq!(manager.secrets_status().mode, Some(KeyMode::KeyFile));
+        assert!(files_with("lt_8f1e2d").is_empty(), "明文残留: {:?}", files_with("lt_8f1e2d"));
+        assert_eq!(key_files(), 1);
+
+        // 换成密码后备份改用新密钥，旧密钥文件被删除
+        manager.rekey_secrets(KeyMode::Passphrase, Some("correct horse"), false).unwrap();
+        assert_eq!(key_files(), 0);
+        let header = manager.config.lock().unwrap().secrets.clone().unwrap();
+        let key = config_secrets::open(&header, Some("correct horse"), &dir).unwrap();
+        for backup in config_store::backup_paths(&config_path) {
+            let (mut config, _) = parse_config(&fs::read_to_string(&backup).unwrap()).unwrap();
+            assert_eq!(config.secrets.as_ref().map(|h| &h.key_id), Some(&header.key_id));
+            assert!(!open_accounts(&mut config, &key));
+            assert_eq!(
+                config.browser_accounts["2c9c29a2a6f9"].link_token.as_deref(),
+                Some("lt_8f1e2d")
+            );
+        }
+    }
+
+    #[test]
+    fn test_watcher_can_save_during_external_change() {
+        let dir = temp_dir("reentrant-watcher");
+        let manager = Arc::new(ConfigManager::open(dir.clone()));
+        manager.set_string("username", "a".to_string()).unwrap();
+
+        // 回调中修改另一个配置项（会再次保存配置文件）
+        let weak = Arc::downgrade(&manager);
+        manager
+            .subscribe("username", move |value| {
+                if let (Some(manager), Some("bb")) = (weak.upgrade(), value.as_str()) {
+                    manager.set_bool("member_mode", true).unwrap();
+                }
+            })
+            .unwrap();
+
+        let config_path = dir.join(CONFIG_FILE_NAME);
+        let content = fs::read_to_string(&config_path).unwrap();
+        fs::write(&config_path, content.replace("\"username\": \"a\"", "\"username\": \"bb\"")).unwrap();
+
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        let checker = manager.clone();
+        std::thread::spawn(move || {
+            checker.check_external_changes();
+            done_tx.send(()).unwrap();
+        });
+        done_rx.recv_timeout(Duration::from_secs(5)).expect("watcher deadlocked");
+
+        assert_eq!(manager.get_string("username").as_deref(), Some("bb"));
+        assert_eq!(manager.get_bool("member_mode"), Some(true));
+        let saved = fs::read_to_string(&config_path).unwrap();
+        assert!(saved.contains("\"username\": \"bb\"") && saved.contains("\"member_mode\": true"));
+    }
+
+    #[test]
+    fn test_nested_key_lookup_and_assign() {
+        let mut doc = serde_json::json!({ "username": "a" });
+
+        assign(&mut doc, "bitbrowser_launch.window", Value::from("hidden"));
+        assert_eq!(lookup(&doc, "bitbrowser_launch.window"), Some(&Value::from("hidden")));
+        let config: AppConfig = serde_json::from_value(doc.clone()).unwrap();
+        assert_eq!(
+            config.bitbrowser_launch.unwrap().window,
+            crate::bitbrowser_launch_profile::WindowMode::Hidden
+        );
+
+        // null 删除字段，反序列化时恢复默认值
+        assign(&mut doc, "bitbrowser_launch.window", Value::Null);
+        assign(&mut doc, "username", Value::Null);
+        assert_eq!(lookup(&doc, "bitbrowser_launch.window"), None);
+        assert_eq!(lookup(&doc, "username"), None);
+        assert!(serde_json::from_value::<AppConfig>(doc).is_ok());
+    }
+
+    #[test]
+    fn test_keys_overlap() {
+        assert!(keys_overlap("bitbrowser_api", "bitbrowser_api"));
+        assert!(keys_overlap("bitbrowser_launch", "bitbrowser_launch.window"));
+        assert!(keys_overlap("bitbrowser_launch.window", "bitbrowser_launch"));
+        assert!(!keys_overlap("bitbrowser_api", "bitbrowser_api_token"));
+        assert!(!keys_overlap("bitbrowser_launch.window", "bitbrowser_launch.env"));
+    }
+
+    #[test]
+    fn test_migrate_rejects_invalid_documents() {
+        assert!(parse_config("[]").is_err());
+        assert!(parse_config(r#"{"schema_version": "one"}"#).is_err());
+
+        // 更高版本的配置按当前结构读取
+        let (config, from_version) =
+            parse_config(r#"{"schema_version": 99, "username": "a", "future": true}"#).unwrap();
+        assert_eq!(from_version, 99);
+        assert_eq!(config.username.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn test_newer_version_is_read_only() {
+        let dir = temp_dir("newer-version");
+        let config_path = dir.join(CONFIG_FILE_NAME);
+        let content = r#"{"schema_version": 99, "username": "a", "future": {"enabled": true}}"#;
+        fs::write(&config_path, content).unwrap();
+
+        let manager = ConfigManager::open(dir.clone());
+        assert_eq!(manager.load_report().newer_version, Some(99));
+        assert!(manager.load_report().warning.is_some());
+        assert_eq!(manager.get_string("username").as_deref(), Some("a"));
+
+        // 修改被拒绝，内存和文件都保持原样
+        assert!(manager.set_bool("member_mode", true).is_err());
+        assert_eq!(manager.get_bool("member_mode"), Some(false));
+        assert!(manager.delete_account("2c9c29a2a6f9").is_err());
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), content);
+        assert!(config_store::backup_paths(&config_path).is_empty());
+    }
+}
@@ -0,0 +1,397 @@
+/**
+ * Config Schema
+ * 配置项注册表：声明 settings.json 中每个配置项的类型、默认值、校验规则和说明
+ *
+ * - 配置项的 key 与 AppConfig 序列化后的字段名一致，嵌套字段用 "." 连接
+ *   （例如 bitbrowser_launch.window）
+ * - ConfigManager 的通用读写方法按注册表校验，新增配置项只需在 AppConfig
+ *   中加字段并在 SETTINGS 中登记
+ * - config_list_schema 命令把注册表返回给前端
+ */
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::bitbrowser_launch_profile::LaunchProfile;
+use crate::bitbrowser_manager::{api_port_from_url, normalize_api_url};
+use crate::bitbrowser_recovery::RecoveryPolicy;
+
+/// 配置项类型
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SettingKind {
+    String,
+    Bool,
+    Number {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        min: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max: Option<f64>,
+        /// 是否只允许整数
+        integer: bool,
+    },
+    Enum {
+        options: &'static [&'static str],
+    },
+    /// JSON 对象（完整内容由 validate 校验）
+    Object,
+    /// 字符串列表
+    StringList,
+    /// 字符串到字符串的映射
+    StringMap,
+}
+
+/// 额外校验函数
+pub type Validator = fn(&Value) -> Result<(), String>;
+
+/// 配置项定义
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingDef {
+    pub key: &'static str,
+    #[serde(flatten)]
+    pub kind: SettingKind,
+    /// 默认值（JSON 字面量，null 表示未设置）
+    #[serde(serialize_with = "serialize_default")]
+    pub default: &'static str,
+    pub description: &'static str,
+    /// 额外校验（类型检查之后执行）
+    #[serde(skip)]
+    pub validate: Option<Validator>,
+}
+
+fn serialize_default<S: serde::Serializer>(default: &&'static str, serializer: S) -> Result<S::Ok, S::Error> {
+    parse_default(default).serialize(serializer)
+}
+
+fn parse_default(default: &str) -> Value {
+    serde_json::from_str(default).unwrap_or(Value::Null)
+}
+
+impl SettingDef {
+    /// 默认值
+    pub fn default_value(&self) -> Value {
+        parse_default(self.default)
+    }
+
+    /// 校验新值，null 表示恢复默认值，总是允许
+    pub fn check(&self, value: &Value) -> Result<(), String> {
+        if value.is_null() {
+            return Ok(());
+        }
+
+        let type_ok = match self.kind {
+            SettingKind::String => value.is_string(),
+            SettingKind::Bool => value.is_boolean(),
+            SettingKind::Number { min, max, integer } => {
+                let number = value
+                    .as_f64()
+                    .ok_or_else(|| format!("配置项 {} 需要数字", self.key))?;
+                if integer && !(value.is_i64() || value.is_u64()) {
+                    return Err(format!("配置项 {} 需要整数", self.key));
+                }
+                if min.map_or(false, |min| number < min) || max.map_or(false, |max| number > max) {
+                    return Err(format!(
+                        "配置项 {} 超出范围 [{}, {}]",
+                        self.key,
+                        min.map_or("-∞".to_string(), |v| v.to_string()),
+                        max.map_or("+∞".to_string(), |v| v.to_string())
+                    ));
+                }
+                true
+            }
+            SettingKind::Enum { options } => {
+                let text = value.as_str().unwrap_or_default();
+                if !options.contains(&text) {
+                    return Err(format!(
+                        "配置项 {} 只能是 {} 之一",
+                        self.key,
+                        options.join(" / ")
+                    ));
+                }
+                true
+            }
+            SettingKind::Object => value.is_object(),
+            SettingKind::StringList => value
+                .as_array()
+                .map_or(false, |items| items.iter().all(Value::is_string)),
+            SettingKind::StringMap => value
+                .as_object()
+                .map_or(false, |map| map.values().all(Value::is_string)),
+        };
+        if !type_ok {
+            return Err(format!("配置项 {} 的类型不正确", self.key));
+        }
+
+        match self.validate {
+            Some(validate) => validate(value),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 所有配置项
+pub const SETTINGS: &[SettingDef] = &[
+    SettingDef {
+        key: "bitbrowser_path",
+        kind: SettingKind::String,
+        default: "null",
+        description: "BitBrowser 可执行文件路径，未设置时自动查找",
+        validate: None,
+    },
+    SettingDef {
+        key: "bitbrowser_api",
+        kind: SettingKind::String,
+        default: "null",
+        description: "BitBrowser API 地址，未设置时自动探测端口",
+        validate: Some(validate_api_url),
+    },
+    SettingDef {
+        key: "bitbrowser_api_token",
+        kind: SettingKind::String,
+        default: "null",
+        description: "BitBrowser API Token，未设置时不鉴权",
+        validate: None,
+    },
+    SettingDef {
+        key: "username",
+        kind: SettingKind::String,
+        default: "null",
+        description: "当前用户名",
+        validate: None,
+    },
+    SettingDef {
+        key: "filter_my_accounts",
+        kind: SettingKind::Bool,
+        default: "true",
+        description: "只显示我的账号",
+        validate: None,
+    },
+    SettingDef {
+        key: "member_mode",
+        kind: SettingKind::Bool,
+        default: "false",
+        description: "成员模式",
+        validate: None,
+    },
+    SettingDef {
+        key: "test_persistence_mode",
+        kind: SettingKind::Bool,
+        default: "false",
+        description: "测试持久化模式",
+        validate: None,
+    },
+    SettingDef {
+        key: "bitbrowser_vip_mode",
+        kind: SettingKind::Bool,
+        default: "true",
+        description: "BitBrowser VIP 模式（API 限速 8 次/秒）",
+        validate: None,
+    },
+    SettingDef {
+        key: "bitbrowser_launch",
+        kind: SettingKind::Object,
+        default: "null",
+        description: "BitBrowser 启动配置",
+        validate: Some(validate_launch_profile),
+    },
+    SettingDef {
+        key: "bitbrowser_launch.args",
+        kind: SettingKind::StringList,
+        default: "[]",
+        description: "BitBrowser 额外启动参数",
+        validate: None,
+    },
+    SettingDef {
+        key: "bitbrowser_launch.workingDir",
+        kind: SettingKind::String,
+        default: "null",
+        description: "BitBrowser 工作目录，未设置时继承本程序的工作目录",
+        validate: Some(validate_working_dir),
+    },
+    SettingDef {
+        key: "bitbrowser_launch.env",
+        kind: SettingKind::StringMap,
+        default: "{}",
+        description: "BitBrowser 额外环境变量",
+        validate: None,
+    },
+    SettingDef {
+        key: "bitbrowser_launch.window",
+        kind: SettingKind::Enum {
+            options: &["normal", "minimized", "hidden"],
+        },
+        default: "\"normal\"",
+        description: "BitBrowser 窗口模式",
+        validate: None,
+    },
+    SettingDef {
+        key: "bitbrowser_launch.detached",
+        kind: SettingKind::Bool,
+        default: "false",
+        description: "以分离进程启动 BitBrowser",
+        validate: None,
+    },
+    SettingDef {
+        key: "bitbrowser_recovery",
+        kind: SettingKind::Object,
+        default: "null",
+        description: "BitBrowser 自动恢复策略",
+        validate: Some(validate_recovery_policy),
+    },
+    SettingDef {
+        key: "bitbrowser_recovery.enabled",
+        kind: SettingKind::Bool,
+        default: "false",
+        description: "BitBrowser 未运行或卡在初始化时自动启动 / 重启",
+        validate: None,
+    },
+    SettingDef {
+        key: "bitbrowser_recovery.notRunningGraceSecs",
+        kind: SettingKind::Number {
+            min: Some(0.0),
+            max: None,
+            integer: true,
+        },
+        default: "30",
+        description: "BitBrowser 未运行持续多久后自动启动（秒）",
+        validate: None,
+    },
+    SettingDef {
+        key: "bitbrowser_recovery.initializingTimeoutSecs",
+        kind: SettingKind::Number {
+            min: Some(0.0),
+            max: None,
+            integer: true,
+        },
+        default: "180",
+        description: "BitBrowser 初始化持续多久视为卡住（秒）",
+        validate: None,
+    },
+    SettingDef {
+        key: "bitbrowser_recovery.unresponsiveDeadlineSecs",
+        kind: SettingKind::Number {
+            min: Some(1.0),
+            max: None,
+            integer: true,
+        },
+        default: "120",
+        description: "恢复动作执行后，多久仍未连接则再次尝试（秒）",
+        validate: None,
+    },
+    SettingDef {
+        key: "bitbrowser_recovery.maxAttempts",
+        kind: SettingKind::Number {
+            min: Some(0.0),
+            max: None,
+            integer: true,
+        },
+        default: "3",
+        description: "连接恢复前最多尝试的次数",
+        validate: None,
+    },
+    SettingDef {
+        key: "external_edit_policy",
+        kind: SettingKind::Enum {
+            options: &["merge", "reload"],
+        },
+        default: "\"merge\"",
+        description: "settings.json 被外部修改时的处理方式：merge 合并（冲突时保留程序中的值）/ reload 以文件为准",
+        validate: None,
+    },
+];
+
+/// 查找配置项
+pub fn find(key: &str) -> Result<&'static SettingDef, String> {
+    SETTINGS
+        .iter()
+        .find(|def| def.key == key)
+        .ok_or_else(|| format!("未知的配置项: {}", key))
+}
+
+fn validate_api_url(value: &Value) -> Result<(), String> {
+    let url = value.as_str().unwrap_or_default();
+    match normalize_api_url(url) {
+        // 空字符串表示自动探测
+        None => Ok(()),
+        Some(normalized) => match api_port_from_url(&normalized) {
+            Some(_) => Ok(()),
+            None => Err(format!("无效的 API 地址: {}", url)),
+        },
+    }
+}
+
+fn validate_launch_profile(value: &Value) -> Result<(), String> {
+    let profile: LaunchProfile =
+        serde_json::from_value(value.clone()).map_err(|e| format!("无效的启动配置: {}", e))?;
+    profile.validate()
+}
+
+fn validate_recovery_policy(value: &Value) -> Result<(), String> {
+    let policy: RecoveryPolicy =
+        serde_json::from_value(value.clone()).map_err(|e| format!("无效的自动恢复策略: {}", e))?;
+    policy.validate()
+}
+
+/// 与完整的启动配置使用同一套校验
+fn validate_working_dir(value: &Value) -> Result<(), String> {
+    LaunchProfile {
+        working_dir: Some(value.as_str().unwrap_or_default().to_string()),
+        ..Default::default()
+    }
+    .validate()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_registry_is_consistent() {
+        for def in SETTINGS {
+            assert!(def.check(&def.default_value()).is_ok(), "{} 的默认值无效", def.key);
+            assert_eq!(find(def.key).unwrap().key, def.key);
+        }
+        assert!(find("unknown").is_err());
+    }
+
+    #[test]
+    fn test_check_types() {
+        let window = find("bitbrowser_launch.window").unwrap();
+        assert!(window.check(&json!("hidden")).is_ok());
+        assert!(window.check(&json!("fullscreen")).is_err());
+
+        let recovery = find("bitbrowser_recovery").unwrap();
+        assert!(recovery.check(&json!({ "enabled": true, "maxAttempts": 5 })).is_ok());
+        assert!(recovery.check(&json!({ "enabled": true, "unresponsiveDeadlineSecs": 0 })).is_err());
+        assert!(find("bitbrowser_recovery.maxAttempts").unwrap().check(&json!(-1)).is_err());
+
+        let api = find("bitbrowser_api").unwrap();
+        assert!(api.check(&json!("127.0.0.1:54345")).is_ok());
+        assert!(api.check(&json!("")).is_ok());
+        assert!(api.check(&json!("127.0.0.1:port")).is_err());
+        assert!(api.check(&json!(54345)).is_err());
+        assert!(api.check(&Value::Null).is_ok());
+
+        let number = SettingDef {
+            key: "interval",
+            kind: SettingKind::Number {
+                min: Some(1.0),
+                max: Some(60.0),
+                integer: true,
+            },
+            default: "5",
+            description: "",
+            validate: None,
+        };
+        assert!(number.check(&json!(30)).is_ok());
+        assert!(number.check(&json!(0)).is_err());
+        assert!(number.check(&json!(1.5)).is_err());
+        assert!(number.check(&json!("30")).is_err());
+
+        let schema = serde_json::to_value(number).unwrap();
+        assert_eq!(schema["type"], "number");
+        assert_eq!(schema["default"], 5);
+        assert_eq!(schema["max"], 60.0);
+    }
+}
@@ -0,0 +1,268 @@
+/**
+ * Config Secrets
+ * 敏感配置加密：settings.json 中的敏感字段（账号的 linkToken 等）加密后保存
+ *
+ * - 加密：XChaCha20-Poly1305，密文格式为 "enc:v1:" + base64(nonce || 密文)
+ * - 密钥：由用户密码或本机密钥文件经 Argon2id 派生
+ *   - 密码模式：启动后需要输入密码解锁，解锁前敏感字段保持密文
+ *   - 密钥文件模式（默认）：随机生成的密钥保存在本机配置目录的 secrets-<id>.key 中，
+ *     不写入 settings.json，单独复制 settings.json 无法解密
+ * - 密钥信息（模式、盐、校验值）保存在 settings.json 的 secrets 中，不包含密钥本身
+ */
+use base64::{engine::general_purpose, Engine as _};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config_store;
+
+/// 密文前缀（带格式版本）
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// 用于校验密钥是否正确的明文
+const CHECK_PLAINTEXT: &str = "video-toolbox-secrets";
+
+/// 密码最短长度
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// 密钥来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyMode {
+    /// 用户密码
+    Passphrase,
+    /// 本机密钥文件
+    KeyFile,
+}
+
+/// 密钥信息（保存在 settings.json 中）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretsHeader {
+    pub mode: KeyMode,
+    /// 密钥 ID，更换密钥时重新生成（密钥文件按 ID 命名）
+    pub key_id: String,
+    /// Argon2 盐（base64）
+    pub salt: String,
+    /// 用该密钥加密的校验值，用于判断密码是否正确
+    pub check: String,
+}
+
+/// 派生出的密钥
+#[derive(Clone)]
+pub struct SecretKey([u8; KEY_LEN]);
+
+impl SecretKey {
+    fn derive(secret: &[u8], salt: &[u8]) -> Result<Self, String> {
+        let mut key = [0u8; KEY_LEN];
+        argon2::Argon2::default()
+            .hash_password_into(secret, salt, &mut key)
+            .map_err(|e| format!("派生密钥失败: {}", e))?;
+        Ok(SecretKey(key))
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(&self.0.into())
+    }
+
+    /// 加密，返回带前缀的密文
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, String> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher()
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| "加密失败".to_string())?;
+
+        let mut payload = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        payload.extend_from_slice(&nonce);
+        payload.extend_from_slice(&ciphertext);
+        Ok(format!("{}{}", ENCRYPTED_PREFIX, general_purpose::STANDARD.encode(payload)))
+    }
+
+    /// 解密带前缀的密文
+    pub fn decrypt(&self, value: &str) -> Result<String, String> {
+        let encoded = value.strip_prefix(ENCRYPTED_PREFIX).ok_or("不是加密数据")?;
+        let payload = general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| format!("密文格式错误: {}", e))?;
+        if payload.len() < NONCE_LEN {
+            return Err("密文长度错误".to_string());
+        }
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher()
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "解密失败（密钥不正确或数据已损坏）".to_string())?;
+        String::from_utf8(plaintext).map_err(|_| "解密结果不是有效的文本".to_string())
+    }
+}
+
+/// 是否是加密后的值
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// 生成新密钥（密钥文件模式会在 dir 中创建密钥文件）
+pub fn generate(mode: KeyMode, passphrase: Option<&str>, dir: &Path) -> Result<(SecretsHeader, SecretKey), String> {
+    let key_id = uuid::Uuid::new_v4().simple().to_string();
+    let secret = match mode {
+        KeyMode::Passphrase => {
+            let passphrase = passphrase.unwrap_or_default();
+            if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+                return Err(format!("密码至少需要 {} 个字符", MIN_PASSPHRASE_LEN));
+            }
+            passphrase.as_bytes().to_vec()
+        }
+        KeyMode::KeyFile => {
+            let mut secret = vec![0u8; KEY_LEN];
+            OsRng.fill_bytes(&mut secret);
+            write_key_file(&key_file_path(dir, &key_id), &secret)?;
+            secret
+        }
+    };
+
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = SecretKey::derive(&secret, &salt)?;
+    let header = SecretsHeader {
+        mode,
+        key_id,
+        salt: general_purpose::STANDARD.encode(salt),
+        check: key.encrypt(CHECK_PLAINTEXT)?,
+    };
+    Ok((header, key))
+}
+
+/// 按密钥信息恢复密钥（密码模式需要提供密码）
+pub fn open(header: &SecretsHeader, passphrase: Option<&str>, dir: &Path) -> Result<SecretKey, String> {
+    let secret = match header.mode {
+        KeyMode::Passphrase => passphrase.ok_or("需要输入密码解锁")?.as_bytes().to_vec(),
+        KeyMode::KeyFile => {
+            let path = key_file_path(dir, &header.key_id);
+            let content = fs::read_to_string(&path)
+                .map_err(|e| format!("读取密钥文件 {} 失败: {}", path.display(), e))?;
+            general_purpose::STANDARD
+                .decode(content.trim())
+                .map_err(|e| format!("密钥文件格式错误: {}", e))?
+        }
+    };
+
+    let salt = general_purpose::STANDARD
+        .decode(&header.salt)
+        .map_err(|e| format!("密钥信息格式错误: {}", e))?;
+    let key = SecretKey::derive(&secret, &salt)?;
+    match key.decrypt(&header.check) {
+        Ok(check) if check == CHECK_PLAINTEXT => Ok(key),
+        _ => Err(match header.mode {
+            KeyMode::Passphrase => "密码错误".to_string(),
+            KeyMode::KeyFile => "密钥文件与配置不匹配".to_string(),
+        }),
+    }
+}
+
+/// 删除不再使用的密钥文件
+pub fn remove_key_file(header: &SecretsHeader, dir: &Path) {
+    if header.mode == KeyMode::KeyFile {
+        let _ = fs::remove_file(key_file_path(dir, &header.key_id));
+    }
+}
+
+/// 删除 used 以外的密钥文件（备份仍在引用的旧密钥需要保留）
+pub fn remove_unused_key_files(dir: &Path, used: &[String]) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let key_id = match name.strip_prefix("secrets-").and_then(|rest| rest.strip_suffix(".key")) {
+            Some(key_id) => key_id,
+            None => continue,
+        };
+        if !used.iter().any(|used| used == key_id) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+fn key_file_path(dir: &Path, key_id: &str) -> PathBuf {
+    dir.join(format!("secrets-{}.key", key_id))
+}
+
+/// 保存密钥文件（仅当前用户可读写）
+fn write_key_file(path: &Path, secret: &[u8]) -> Result<(), String> {
+    config_store::write_private(path, general_purpose::STANDARD.encode(secret).as_bytes())
+        .map_err(|e| format!("保存密钥文件失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("config-secrets-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let key = SecretKey::derive(b"secret", b"saltsaltsalt").unwrap();
+        let other = SecretKey::derive(b"other", b"saltsaltsalt").unwrap();
+
+        let encrypted = key.encrypt("lt_8f1e2d").unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.contains("lt_8f1e2d"));
+        // 每次加密使用新的 nonce
+        assert_ne!(encrypted, key.encrypt("lt_8f1e2d").unwrap());
+
+        assert_eq!(key.decrypt(&encrypted).unwrap(), "lt_8f1e2d");
+        assert!(other.decrypt(&encrypted).is_err());
+        assert!(key.decrypt("lt_8f1e2d").is_err());
+        assert!(key.decrypt("enc:v1:AAAA").is_err());
+    }
+
+    #[test]
+    fn test_passphrase_key() {
+        let dir = temp_dir("passphrase");
+        assert!(generate(KeyMode::Passphrase, Some("short"), &dir).is_err());
+
+        let (header, key) = generate(KeyMode::Passphrase, Some("correct horse"), &dir).unwrap();
+        let encrypted = key.encrypt("lt_8f1e2d").unwrap();
+
+        let reopened = open(&header, Some("correct horse"), &dir).unwrap();
+        assert_eq!(reopened.decrypt(&encrypted).unwrap(), "lt_8f1e2d");
+        assert_eq!(open(&header, Some("wrong horse"), &dir).err().unwrap(), "密码错误");
+        assert!(open(&header, None, &dir).is_err());
+    }
+
+    #[test]
+    fn test_key_file() {
+        let dir = temp_dir("key-file");
+        let (header, key) = generate(KeyMode::KeyFile, None, &dir).unwrap();
+        let encrypted = key.encrypt("lt_8f1e2d").unwrap();
+
+        let reopened = open(&header, None, &dir).unwrap();
+        assert_eq!(reopened.decrypt(&encrypted).unwrap(), "lt_8f1e2d");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(key_file_path(&dir, &header.key_id)).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // 密钥文件丢失时无法解密
+        remove_key_file(&header, &dir);
+        assert!(open(&header, None, &dir).is_err());
+    }
+}
@@ -0,0 +1,459 @@
+/**
+ * Config Store
+ * 配置文件存储：settings.json 的原子写入、备份轮换和损坏恢复
+ *
+ * - 写入：先写临时文件并 fsync，再 rename 覆盖，崩溃时不会留下半截文件
+ * - 备份：每次写入前把当前有效的文件轮换到 settings.json.bak.1 ~ .bak.N
+ * - 读取：文件损坏时保留一份 .corrupt 副本，并从最新的有效备份恢复
+ */
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 保留的备份数量
+pub const BACKUP_COUNT: usize = 3;
+
+/// 读取结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadStatus {
+    /// 正常读取
+    Loaded,
+    /// 配置文件不存在（首次运行）
+    Missing,
+    /// 配置文件损坏或丢失，已从备份恢复
+    Recovered,
+    /// 配置文件损坏且没有可用的备份，使用默认配置
+    Failed,
+}
+
+/// 读取报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadReport {
+    pub status: LoadStatus,
+    /// 配置文件的错误信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 用于恢复的备份文件
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup: Option<String>,
+    /// 损坏文件的保留副本
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub corrupt_copy: Option<String>,
+    /// 从哪个旧版本升级而来（由调用方填写）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub migrated_from: Option<u32>,
+    /// 由更新版本的程序写入时的版本号，此时配置文件只读（由调用方填写）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub newer_version: Option<u32>,
+    /// 需要提示用户的警告（由调用方填写，见 warning_message）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+impl LoadReport {
+    fn new(status: LoadStatus) -> Self {
+        LoadReport {
+            status,
+            error: None,
+            backup: None,
+            corrupt_copy: None,
+            migrated_from: None,
+            newer_version: None,
+            warning: None,
+        }
+    }
+
+    /// 生成需要提示用户的警告：从备份恢复、使用默认配置或配置只读，正常读取时为 None
+    pub fn warning_message(&self) -> Option<String> {
+        let error = self.error.as_deref().unwrap_or("未知错误");
+        let kept = match &self.corrupt_copy {
+            Some(copy) => format!("，损坏的文件已保留为 {}", copy),
+            None => String::new(),
+        };
+        match self.status {
+            LoadStatus::Recovered => Some(format!(
+                "配置文件损坏（{}），已从备份 {} 恢复{}",
+                error,
+                self.backup.as_deref().unwrap_or(""),
+                kept
+            )),
+            LoadStatus::Failed => Some(format!("配置文件损坏（{}）且没有可用的备份，已使用默认配置{}", error, kept)),
+            LoadStatus::Loaded | LoadStatus::Missing => self.newer_version.map(|version| {
+                format!("配置文件由更新版本的程序写入（版本 {}），修改不会保存", version)
+            }),
+        }
+    }
+}
+
+/// 第 index 个备份文件的路径（1 为最新）
+fn backup_path(path: &Path, index: usize) -> PathBuf {
+    append_extension(path, &format!("bak.{}", index))
+}
+
+/// 现有的备份文件（从新到旧）
+pub fn backup_paths(path: &Path) -> Vec<PathBuf> {
+    (1..=BACKUP_COUNT)
+        .map(|index| backup_path(path, index))
+        .filter(|backup| backup.exists())
+        .collect()
+}
+
+/// 现有的损坏文件副本
+pub fn corrupt_copies(path: &Path) -> Vec<PathBuf> {
+    let prefix = append_extension(path, "corrupt-");
+    let prefix = prefix.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|copy| {
+                    copy.file_name()
+                        .map_or(false, |name| name.to_string_lossy().starts_with(&prefix))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn append_extension(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// 原子写入：写临时文件 → fsync → rename
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    write_atomic_with(path, contents, false)
+}
+
+/// 原子写入仅当前用户可读写的文件（Unix 下临时文件创建时即为 0600，内容不会短暂地对其他用户可见）
+pub fn write_private(path: &Path, contents: &[u8]) -> Result<(), String> {
+    write_atomic_with(path, contents, true)
+}
+
+fn create_temp_file(path: &Path, private: bool) -> std::io::Result<File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        // 权限只在创建时生效，先删除可能残留的临时文件
+        let _ = fs::remove_file(path);
+        options.mode(0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+
+    options.open(path)
+}
+
+fn write_atomic_with(path: &Path, contents: &[u8], private: bool) -> Result<(), String> {
+    let tmp_path = append_extension(path, "tmp");
+
+    let mut file = create_temp_file(&tmp_path, private).map_err(|e| format!("创建临时文件失败: {}", e))?;
+    file.write_all(contents)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| {
+            let _ = fs::remove_file(&tmp_path);
+            format!("写入临时文件失败: {}", e)
+        })?;
+    drop(file);
+
+    fs::rename(&tmp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        format!("替换配置文件失败: {}", e)
+    })?;
+
+    // 确保 rename 本身落盘
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    Ok(())
+}
+
+/// 轮换备份：.bak.N-1 → .bak.N，…，当前文件 → .bak.1
+///
+/// 当前文件无效时不参与轮换，避免用损坏的内容覆盖有效备份
+fn rotate_backups<T: DeserializeOwned>(path: &Path) {
+    if read_valid::<T>(path).is_err() {
+        return;
+    }
+
+    for index in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(path, index);
+        if from.exists() {
+            let _ = fs::rename(&from, backup_path(path, index + 1));
+        }
+    }
+    if let Err(e) = fs::copy(path, backup_path(path, 1)) {
+        println!("⚠ 备份配置文件失败: {}", e);
+    }
+}
+
+/// 保存：轮换备份后原子写入
+pub fn save<T: Serialize + DeserializeOwned>(path: &Path, value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| format!("序列化配置失败: {}", e))?;
+    rotate_backups::<T>(path);
+    write_atomic(path, json.as_bytes()).map_err(|e| format!("写入配置文件失败: {}", e))
+}
+
+/// 读取并解析文件
+fn read_valid<T: DeserializeOwned>(path: &Path) -> Result<(T, String), String> {
+    read_with(path, &parse_json)
+}
+
+fn read_with<T>(path: &Path, parse: &impl Fn(&str) -> Result<T, String>) -> Result<(T, String), String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("读取失败: {}", e))?;
+    let value = parse(&content)?;
+    Ok((value, content))
+}
+
+fn parse_json<T: DeserializeOwned>(content: &str) -> Result<T, String> {
+    serde_json::from_str(content).map_err(|e| format!("解析失败: {}", e))
+}
+
+/// 读取配置，损坏或丢失时从最新的有效备份恢复
+///
+/// parse 负责解析文件内容（例如先升级旧版本再反序列化），解析失败的文件视为损坏。
+/// 返回 None 表示没有可用的配置（调用方使用默认配置）
+pub fn load<T>(path: &Path, parse: impl Fn(&str) -> Result<T, String>) -> (Option<T>, LoadReport) {
+    let mut report = if path.exists() {
+        match read_with(path, &parse) {
+            Ok((value, _)) => return (Some(value), LoadReport::new(LoadStatus::Loaded)),
+            Err(e) => {
+                println!("✗ 配置文件已损坏: {}", e);
+                let mut report = LoadReport::new(LoadStatus::Failed);
+                report.error = Some(e);
+                report.corrupt_copy = preserve_corrupt(path);
+                report
+            }
+        }
+    } else {
+        LoadReport::new(LoadStatus::Missing)
+    };
+
+    for index in 1..=BACKUP_COUNT {
+        let backup = backup_path(path, index);
+        let (value, content) = match read_with(&backup, &parse) {
+            Ok(found) => found,
+            Err(_) => continue,
+        };
+
+        if let Err(e) = write_atomic(path, content.as_bytes()) {
+            println!("⚠ 从备份恢复配置文件失败: {}", e);
+        }
+        println!("✓ 已从备份恢复配置: {}", backup.display());
+        report.status = LoadStatus::Recovered;
+        report.backup = Some(backup.to_string_lossy().to_string());
+        return (Some(value), report);
+    }
+
+    if report.status == LoadStatus::Failed {
+        println!("✗ 没有可用的配置备份，使用默认配置");
+    }
+    (None, report)
+}
+
+/// 文件指纹（大小和修改时间），用于发现外部修改
+pub fn fingerprint(path: &Path) -> Option<(u64, SystemTime)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+/// 三方合并：base 为上次读写时磁盘上的内容，ours 为内存中的内容，theirs 为磁盘上的新内容
+///
+/// 只有一方修改的字段取修改后的值；双方都是对象时逐个字段合并；
+/// 双方都修改且结果不同的字段记为冲突（路径用 "." 连接），保留 ours。
+pub fn merge_documents(
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    path: &str,
+    conflicts: &mut Vec<String>,
+) -> Option<Value> {
+    if ours == theirs || theirs == base {
+        return ours.cloned();
+    }
+    if ours == base {
+        return theirs.cloned();
+    }
+
+    if let (Some(Value::Object(ours_map)), Some(Value::Object(theirs_map))) = (ours, theirs) {
+        let base_map = base.and_then(Value::as_object);
+        let mut keys: Vec<&String> = ours_map.keys().chain(theirs_map.keys()).collect();
+        keys.sort();
+        keys.dedup();
+
+        let mut merged = Map::new();
+        for key in keys {
+            let child_path = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            let value = merge_documents(
+                base_map.and_then(|m| m.get(key)),
+                ours_map.get(key),
+                theirs_map.get(key),
+                &child_path,
+                conflicts,
+            );
+            if let Some(value) = value {
+                merged.insert(key.clone(), value);
+            }
+        }
+        return Some(Value::Object(merged));
+    }
+
+    conflicts.push(path.to_string());
+    ours.cloned()
+}
+
+/// 保留损坏文件的副本，便于人工恢复
+fn preserve_corrupt(path: &Path) -> Option<String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let corrupt = append_extension(path, &format!("corrupt-{}", timestamp));
+    match fs::copy(path, &corrupt) {
+        Ok(_) => Some(corrupt.to_string_lossy().to_string()),
+        Err(e) => {
+            println!("⚠ 保留损坏的配置文件失败: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    type Settings = HashMap<String, u32>;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("config-store-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn settings(version: u32) -> Settings {
+        HashMap::from([("version".to_string(), version)])
+    }
+
+    #[test]
+    fn test_save_rotates_backups() {
+        let dir = temp_dir("rotate");
+        let path = dir.join("settings.json");
+
+        for version in 1..=5 {
+            save(&path, &settings(version)).unwrap();
+        }
+
+        assert_eq!(read_valid::<Settings>(&path).unwrap().0, settings(5));
+        assert_eq!(read_valid::<Settings>(&backup_path(&path, 1)).unwrap().0, settings(4));
+        assert_eq!(read_valid::<Settings>(&backup_path(&path, 3)).unwrap().0, settings(2));
+        assert!(!backup_path(&path, 4).exists());
+        assert!(!append_extension(&path, "tmp").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_recovers_from_newest_valid_backup() {
+        let dir = temp_dir("recover");
+        let path = dir.join("settings.json");
+
+        save(&path, &settings(1)).unwrap();
+        save(&path, &settings(2)).unwrap();
+        save(&path, &settings(3)).unwrap();
+        // 最新的备份也损坏了
+        fs::write(backup_path(&path, 1), "{\"version\": ").unwrap();
+        fs::write(&path, "{\"vers").unwrap();
+
+        let (value, report) = load(&path, parse_json::<Settings>);
+        assert_eq!(value, Some(settings(1)));
+        assert_eq!(report.status, LoadStatus::Recovered);
+        assert!(report.warning_message().unwrap().contains("settings.json.bak.2"));
+        assert!(report.backup.unwrap().ends_with("settings.json.bak.2"));
+        assert_eq!(fs::read_to_string(report.corrupt_copy.unwrap()).unwrap(), "{\"vers");
+
+        // 恢复后的文件可以正常读取
+        let (value, report) = load(&path, parse_json::<Settings>);
+        assert_eq!(value, Some(settings(1)));
+        assert_eq!(report.status, LoadStatus::Loaded);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_merge_documents() {
+        let base = serde_json::json!({
+            "username": "a",
+            "member_mode": false,
+            "browser_accounts": { "1": { "updatedAt": "t1" } }
+        });
+        // 内存中修改了 member_mode，外部修改了 username 并新增账号
+        let ours = serde_json::json!({
+            "username": "a",
+            "member_mode": true,
+            "browser_accounts": { "1": { "updatedAt": "t1" } }
+        });
+        let theirs = serde_json::json!({
+            "username": "b",
+            "member_mode": false,
+            "browser_accounts": { "1": { "updatedAt": "t1" }, "2": { "updatedAt": "t2" } }
+        });
+
+        let mut conflicts = Vec::new();
+        let merged = merge_documents(Some(&base), Some(&ours), Some(&theirs), "", &mut conflicts).unwrap();
+        assert!(conflicts.is_empty());
+        assert_eq!(merged["username"], "b");
+        assert_eq!(merged["member_mode"], true);
+        assert_eq!(merged["browser_accounts"]["2"]["updatedAt"], "t2");
+
+        // 双方修改了同一个账号
+        let ours = serde_json::json!({ "browser_accounts": { "1": { "updatedAt": "mine" } } });
+        let theirs = serde_json::json!({ "browser_accounts": { "1": { "updatedAt": "theirs" } } });
+        let base = serde_json::json!({ "browser_accounts": { "1": { "updatedAt": "t1" } } });
+        let merged = merge_documents(Some(&base), Some(&ours), Some(&theirs), "", &mut conflicts).unwrap();
+        assert_eq!(conflicts, vec!["browser_accounts.1.updatedAt".to_string()]);
+        assert_eq!(merged, ours);
+    }
+
+    #[test]
+    fn test_load_without_backups() {
+        let dir = temp_dir("missing");
+        let path = dir.join("settings.json");
+
+        let (value, report) = load(&path, parse_json::<Settings>);
+        assert_eq!(value, None);
+        assert_eq!(report.status, LoadStatus::Missing);
+        assert_eq!(report.warning_message(), None);
+
+        fs::write(&path, "not json").unwrap();
+        let (value, report) = load(&path, parse_json::<Settings>);
+        assert_eq!(value, None);
+        assert_eq!(report.status, LoadStatus::Failed);
+        assert!(report.error.is_some());
+        assert!(report.warning_message().unwrap().contains("默认配置"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -6,21 +6,44 @@
 
 // BitBrowser 管理模块
 mod bitbrowser_manager;
+mod bitbrowser_cache;
 
 // BitBrowser 新架构模块
+mod bitbrowser_client;
 mod bitbrowser_detector;
+mod bitbrowser_discovery;
+mod bitbrowser_history;
+mod bitbrowser_launch_profile;
 mod bitbrowser_launcher;
 mod bitbrowser_monitor;
+mod bitbrowser_port;
+mod bitbrowser_recovery;
+mod bitbrowser_search;
+mod bitbrowser_shutdown;
+mod bitbrowser_version;
+mod bitbrowser_profile;
 
 // 配置管理模块
 mod config_manager;
+mod config_schema;
+mod config_secrets;
+mod config_store;
+mod port_owner;
+#[cfg(any(target_os = "windows", test))]
+mod shell_link;
 
 use base64::{engine::general_purpose, Engine as _};
+use bitbrowser_client::{
+    BitBrowserClient, BitBrowserError, BrowserCookie, BrowserListRequest,
+    BrowserProfile, OpenBrowserRequest, PageRequest, MAX_PAGE_SIZE,
+};
+use bitbrowser_launcher::WaitOptions;
+use bitbrowser_profile::ProfilePatch;
+use bitbrowser_shutdown::ShutdownOptions;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::process::Command;
-use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Mutex};
 use tauri::Manager;
 
@@ -52,26 +75,27 @@ struct AppState {
     checking_cookies: Mutex<HashSet<String>>,
     // 比特浏览器连接状态
     bitbrowser_connected: Mutex<bool>,
-    // 后台监控任务运行标志
-    monitor_running: Arc<AtomicBool>,
+    // 后台监控任务控制器（启动/停止监控循环，保存监控状态）
+    monitor: bitbrowser_monitor::MonitorController,
+    search: bitbrowser_search::SearchController,
 }
 
 // ==================== 辅助函数 ====================
 
-/// 获取比特浏览器 API 基础 URL（带错误处理）
-async fn get_bb_api_url() -> Result<String, String> {
-    bitbrowser_manager::get_api_base_url().await
-}
-
-/// 创建 HTTP 客户端（禁用代理以访问 localhost）
+/// 将 BitBrowser 客户端错误转换为命令返回值
 ///
-/// 重要：reqwest 默认会使用系统代理，即使访问 localhost 也可能通过代理，
-/// 导致 502 错误。使用 .no_proxy() 禁用代理可以解决此问题。
-fn create_http_client() -> reqwest::Client {
-    reqwest::Client::builder()
-        .no_proxy()  // 禁用代理（关键！）
-        .build()
-        .expect("Failed to create HTTP client")
+/// BitBrowser 明确拒绝的请求（HTTP 状态异常或 success=false）返回 success=false，
+/// 连接失败和响应格式异常直接作为错误返回
+fn bb_failure(error: BitBrowserError) -> Result<ApiResponse, String> {
+    if error.is_rejected() {
+        Ok(ApiResponse {
+            success: false,
+            message: error.to_string(),
+            data: None,
+        })
+    } else {
+        Err(error.to_string())
+    }
 }
 
 /// 创建带超时的 HTTP 客户端
@@ -91,7 +115,7 @@ async fn check_bitbrowser_status() -> Result<ApiResponse, String> {
     let status = bitbrowser_detector::check_status().await;
 
     match status {
-        ConnectionStatus::Connected { message } => Ok(ApiResponse {
+        ConnectionStatus::Connected { message, .. } => Ok(ApiResponse {
             success: true,
             message,
             data: None,
@@ -113,36 +137,19 @@ async fn check_bitbrowser_running() -> Result<ApiResponse, String> {
 // 打开比特浏览器窗口
 #[tauri::command]
 async fn open_bitbrowser(browser_id: String) -> Result<ApiResponse, String> {
-    let base_url = get_bb_api_url().await?;
-    let client = create_http_client();
-    match client
-        .post(format!("{}/browser/open", base_url))
-        .json(&serde_json::json!({
-            "id": browser_id
-        }))
-        .send()
-        .await
-    {
-        Ok(response) => {
-            if response.status().is_success() {
-                Ok(ApiResponse {
-                    success: true,
-                    message: "浏览器打开成功".to_string(),
-                    data: None,
-                })
-            } else {
-                Ok(ApiResponse {
-                    success: false,
-                    message: "浏览器打开失败".to_string(),
-                    data: None,
-                })
-            }
-        }
-        Err(e) => Ok(ApiResponse {
-            success: false,
-            message: format!("API调用失败: {}", e),
+    let client = BitBrowserClient::connect().await?;
+    let request = OpenBrowserRequest {
+        id: browser_id,
+        ..Default::default()
+    };
+
+    match client.open_browser(&request).await {
+        Ok(_) => Ok(ApiResponse {
+            success: true,
+            message: "浏览器打开成功".to_string(),
             data: None,
         }),
+        Err(e) => bb_failure(e),
     }
 }
 
@@ -211,12 +218,105 @@ fn update_bitbrowser_status(state: tauri::State<AppState>, connected: bool) {
     *status = connected;
 }
 
+// 立即重新检测 BitBrowser 连接状态（结果通过 bitbrowser-status 事件推送）
+#[tauri::command]
+fn recheck_now(state: tauri::State<AppState>) {
+    state.monitor.state().request_recheck();
+}
+
+// 暂停后台监控任务（例如批量创建浏览器期间）
+#[tauri::command]
+async fn pause_monitor(state: tauri::State<'_, AppState>) -> Result<ApiResponse, String> {
+    let stopped = state.monitor.stop().await;
+    Ok(ApiResponse {
+        success: true,
+        message: if stopped {
+            "后台监控已暂停".to_string()
+        } else {
+            "后台监控未在运行".to_string()
+        },
+        data: None,
+    })
+}
+
+// 恢复（或重新启动已退出的）后台监控任务
+#[tauri::command]
+fn resume_monitor(app: tauri::AppHandle, state: tauri::State<AppState>) -> Result<ApiResponse, String> {
+    let started = state.monitor.start(app);
+    Ok(ApiResponse {
+        success: true,
+        message: if started {
+            "后台监控已恢复".to_string()
+        } else {
+            "后台监控已在运行".to_string()
+        },
+        data: None,
+    })
+}
+
+// 获取后台监控任务状态和配置
+#[tauri::command]
+fn get_monitor_status(state: tauri::State<AppState>) -> Result<ApiResponse, String> {
+    let monitor = state.monitor.state();
+    Ok(ApiResponse {
+        success: true,
+        message: String::new(),
+        data: Some(serde_json::json!({
+            "running": state.monitor.is_running(),
+            "config": monitor.config(),
+            "lastEvent": monitor.last_event(),
+            "recoveryAttempts": monitor.recovery_attempts(),
+        })),
+    })
+}
+
+// 获取连接状态变化历史和在线统计
+#[tauri::command]
+fn get_connection_history(state: tauri::State<AppState>) -> Result<ApiResponse, String> {
+    let monitor = state.monitor.state();
+    let stats = monitor.stats();
+
+    Ok(ApiResponse {
+        success: true,
+        message: if stats.flapping {
+            format!(
+                "BitBrowser 连接不稳定：最近 10 分钟内状态变化 {} 次",
+                stats.recent_transitions
+            )
+        } else {
+            String::new()
+        },
+        data: Some(serde_json::json!({
+            "history": monitor.history(),
+            "stats": stats,
+        })),
+    })
+}
+
+// 重新配置后台监控的检测间隔（立即生效，未提供的字段保持当前值）
+#[tauri::command]
+fn configure_monitor(
+    config: bitbrowser_monitor::MonitorConfigUpdate,
+    state: tauri::State<AppState>,
+) -> Result<ApiResponse, String> {
+    let config = state.monitor.state().update_config(config)?;
+    Ok(ApiResponse {
+        success: true,
+        message: "监控配置已更新".to_string(),
+        data: Some(serde_json::to_value(config).map_err(|e| e.to_string())?),
+    })
+}
+
 // ==================== BitBrowser 管理命令 ====================
 
-// 查找 BitBrowser 路径
+// 查找 BitBrowser 路径（快速策略，深度搜索见 start_bitbrowser_search）
 #[tauri::command]
-fn find_bitbrowser() -> Result<ApiResponse, String> {
-    match bitbrowser_manager::find_bitbrowser_path() {
+async fn find_bitbrowser() -> Result<ApiResponse, String> {
+    let path = tauri::async_runtime::spawn_blocking(bitbrowser_manager::find_bitbrowser_path)
+        .await
+        .map_err(|e| format!("查找 BitBrowser 失败: {}", e))?;
+
+    match path {
         Some(path) => Ok(ApiResponse {
             success: true,
             message: "找到 BitBrowser".to_string(),
@@ -224,25 +324,113 @@ fn find_bitbrowser() -> Result<ApiResponse, String> {
         }),
         None => Ok(ApiResponse {
             success: false,
-            message: "未找到 BitBrowser 安装路径".to_string(),
+            message: "未找到 BitBrowser 安装路径，可尝试深度搜索".to_string(),
             data: None,
         }),
     }
 }
 
+// 获取深度搜索的默认根目录（供用户选择）
+#[tauri::command]
+fn get_search_roots() -> Result<ApiResponse, String> {
+    Ok(ApiResponse {
+        success: true,
+        message: String::new(),
+        data: Some(serde_json::json!({ "roots": bitbrowser_search::default_roots() })),
+    })
+}
+
+// 启动后台深度搜索（进度通过 bitbrowser-search-progress 事件推送）
+#[tauri::command]
+fn start_bitbrowser_search(
+    options: Option<bitbrowser_search::SearchOptions>,
+    app: tauri::AppHandle,
+    state: tauri::State<AppState>,
+) -> Result<ApiResponse, String> {
+    match state.search.start(app, options.unwrap_or_default()) {
+        Ok(job_id) => Ok(ApiResponse {
+            success: true,
+            message: "深度搜索已开始".to_string(),
+            data: Some(serde_json::json!({ "jobId": job_id })),
+        }),
+        Err(e) => Ok(ApiResponse {
+            success: false,
+            message: e,
+            data: None,
+        }),
+    }
+}
+
+// 取消正在进行的深度搜索
+#[tauri::command]
+fn cancel_bitbrowser_search(state: tauri::State<AppState>) -> Result<ApiResponse, String> {
+    let cancelled = state.search.cancel();
+    Ok(ApiResponse {
+        success: cancelled,
+        message: if cancelled {
+            "深度搜索已取消".to_string()
+        } else {
+            "没有正在进行的深度搜索".to_string()
+        },
+        data: None,
+    })
+}
+
 // 获取 BitBrowser 运行信息
 #[tauri::command]
 fn get_bitbrowser_info() -> Result<ApiResponse, String> {
-    let info = bitbrowser_manager::get_running_bitbrowser_info();
+    let running = bitbrowser_manager::get_running_bitbrowser_info();
+    let is_running = running.is_some();
+
+    // 附带路径缓存，便于排查路径是如何找到的
+    let mut info = running.unwrap_or(bitbrowser_manager::BitBrowserInfo {
+        path: None,
+        is_running: false,
+        process_id: None,
+        discovery: None,
+    });
+    info.discovery = bitbrowser_cache::read();
 
     Ok(ApiResponse {
-        success: info.is_some(),
-        message: if info.is_some() {
+        success: is_running,
+        message: if is_running {
             "BitBrowser 正在运行".to_string()
         } else {
             "BitBrowser 未运行".to_string()
         },
-        data: info.map(|i| serde_json::to_value(i).unwrap()),
+        data: Some(serde_json::to_value(info).map_err(|e| e.to_string())?),
+    })
+}
+
+// 获取 BitBrowser 客户端版本，指定浏览器时附带内核版本和兼容参数
+#[tauri::command]
+async fn get_bitbrowser_version(browser_id: Option<String>) -> Result<ApiResponse, String> {
+    let client_version = bitbrowser_detector::current_client_version();
+
+    let compatibility = match browser_id {
+        Some(id) => {
+            let client = BitBrowserClient::connect().await?;
+            let profile = match client.browser_detail(&id).await {
+                Ok(profile) => profile,
+                Err(e) => return bb_failure(e),
+            };
+            Some(bitbrowser_version::compatibility_for(
+                bitbrowser_version::profile_core_version(&profile),
+            ))
+        }
+        None => None,
+    };
+
+    Ok(ApiResponse {
+        success: client_version.is_some(),
+        message: match &client_version {
+            Some(v) => format!("BitBrowser v{}", v.version),
+            None => "无法检测 BitBrowser 版本".to_string(),
+        },
+        data: Some(serde_json::json!({
+            "client": client_version,
+            "compatibility": compatibility,
+        })),
     })
 }
 
@@ -253,32 +441,43 @@ async fn launch_bitbrowser(path: Option<String>) -> Result<ApiResponse, String>
 
     Ok(ApiResponse {
         success: result.success,
-        message: result.message,
-        data: None,
+        message: result.message.clone(),
+        data: Some(serde_json::to_value(result).map_err(|e| e.to_string())?),
     })
 }
 
-// 停止 BitBrowser
+// 启动 BitBrowser 并等待 API 就绪（通过 bitbrowser-launch 事件推送启动阶段）
 #[tauri::command]
-fn stop_bitbrowser() -> Result<ApiResponse, String> {
-    match bitbrowser_manager::kill_bitbrowser() {
-        Ok(_) => Ok(ApiResponse {
-            success: true,
-            message: "BitBrowser 已停止".to_string(),
-            data: None,
-        }),
-        Err(e) => Ok(ApiResponse {
-            success: false,
-            message: e,
-            data: None,
-        }),
-    }
+async fn launch_bitbrowser_and_wait(
+    path: Option<String>,
+    options: Option<WaitOptions>,
+    app: tauri::AppHandle,
+) -> Result<ApiResponse, String> {
+    let result = bitbrowser_launcher::launch_and_wait(&app, path, &options.unwrap_or_default()).await;
+
+    Ok(ApiResponse {
+        success: result.success,
+        message: result.message.clone(),
+        data: Some(serde_json::to_value(result).map_err(|e| e.to_string())?),
+    })
+}
+
+// 停止 BitBrowser（先关闭浏览器窗口，再请求退出，超时后强制结束）
+#[tauri::command]
+async fn stop_bitbrowser(options: Option<ShutdownOptions>) -> Result<ApiResponse, String> {
+    let report = bitbrowser_shutdown::shutdown(&options.unwrap_or_default()).await;
+
+    Ok(ApiResponse {
+        success: report.success && !report.processes.is_empty(),
+        message: report.summary(),
+        data: Some(serde_json::to_value(&report).map_err(|e| e.to_string())?),
+    })
 }
 
 // 清除 BitBrowser 路径缓存
 #[tauri::command]
 fn clear_bitbrowser_cache() -> Result<ApiResponse, String> {
-    bitbrowser_manager::clear_cached_path();
+    bitbrowser_cache::clear();
     Ok(ApiResponse {
         success: true,
         message: "路径缓存已清除".to_string(),
@@ -319,6 +518,55 @@ fn kill_process_by_pid(pid: u32) -> Result<ApiResponse, String> {
     }
 }
 
+// 检查占用 BitBrowser API 端口的进程（返回进程详情和确认令牌）
+#[tauri::command]
+async fn inspect_port_conflict(port: Option<u16>) -> Result<ApiResponse, String> {
+    let port = match port {
+        Some(port) => port,
+        None => {
+            let base_url = bitbrowser_manager::get_api_base_url().await?;
+            bitbrowser_manager::api_port_from_url(&base_url).unwrap_or(bitbrowser_manager::DEFAULT_API_PORT)
+        }
+    };
+
+    match bitbrowser_port::inspect(port).await? {
+        Some(inspection) => Ok(ApiResponse {
+            success: true,
+            message: match &inspection.owner.protected_reason {
+                Some(reason) => format!("端口 {} 被进程 {} 占用，无法自动处理：{}", port, inspection.owner.name, reason),
+                None => format!("端口 {} 被进程 {} (PID: {}) 占用", port, inspection.owner.name, inspection.owner.pid),
+            },
+            data: Some(serde_json::to_value(inspection).map_err(|e| e.to_string())?),
+        }),
+        None => Ok(ApiResponse {
+            success: true,
+            message: format!("端口 {} 未被占用", port),
+            data: None,
+        }),
+    }
+}
+
+// 凭确认令牌结束占用端口的进程，并验证端口已释放
+#[tauri::command]
+async fn resolve_port_conflict(token: String, state: tauri::State<'_, AppState>) -> Result<ApiResponse, String> {
+    match bitbrowser_port::resolve(&token).await {
+        Ok(resolution) => {
+            // 端口状态已变化，立即重新检测连接
+            state.monitor.state().request_recheck();
+            Ok(ApiResponse {
+                success: resolution.bindable,
+                message: resolution.message.clone(),
+                data: Some(serde_json::to_value(resolution).map_err(|e| e.to_string())?),
+            })
+        }
+        Err(e) => Ok(ApiResponse {
+            success: false,
+            message: e,
+            data: None,
+        }),
+    }
+}
+
 // ==================== 微信登录相关 ====================
 
 // 登录状态存储
@@ -1693,41 +1941,19 @@ async fn validate_shop_helper_cookie(
 }
 
 // 获取下一个浏览器序号
-async fn get_next_browser_seq(client: &reqwest::Client) -> Result<i64, String> {
-    let base_url = get_bb_api_url().await?;
-    // 获取浏览器列表
-    let response = client
-        .post(format!("{}/browser/list", base_url))
-        .json(&serde_json::json!({
-            "page": 0,
-            "pageSize": 1000  // 获取所有浏览器以确定最大序号
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("获取浏览器列表失败: {}", e))?;
-
-    let result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("解析响应失败: {}", e))?;
-
-    if result["success"].as_bool().unwrap_or(false) {
-        // 查找最大的 seq 值
-        let mut max_seq = 0i64;
-        if let Some(list) = result["data"]["list"].as_array() {
-            for browser in list {
-                if let Some(seq) = browser["seq"].as_i64() {
-                    if seq > max_seq {
-                        max_seq = seq;
-                    }
-                }
-            }
+async fn get_next_browser_seq(client: &BitBrowserClient) -> Result<i64, String> {
+    // 遍历所有分页以确定最大序号
+    let mut pages = client.browser_pages(BrowserListRequest::default());
+    let mut max_seq = 0i64;
+
+    while let Some(page) = pages.next_page().await {
+        let page = page.map_err(|e| format!("获取浏览器列表失败: {}", e))?;
+        if let Some(seq) = page.iter().filter_map(|b| b.seq).max() {
+            max_seq = max_seq.max(seq);
         }
-        Ok(max_seq + 1)
-    } else {
-        // 如果获取失败，返回一个较大的默认值
-        Ok(999)
     }
+
+    Ok(max_seq + 1)
 }
 
 // 创建浏览器
@@ -1738,7 +1964,7 @@ async fn create_browser_with_account(
     nickname: Option<String>,
     _app: tauri::AppHandle,
 ) -> Result<serde_json::Value, String> {
-    let client = create_http_client();
+    let client = BitBrowserClient::connect().await?;
 
     // 如果没有提供 nickname，获取下一个序号
     let browser_name = if let Some(name) = nickname {
@@ -1754,181 +1980,67 @@ async fn create_browser_with_account(
         }
     };
 
-    let mut params = serde_json::json!({
-        "name": browser_name,
-        "remark": config["remark"].as_str().unwrap_or(""),
-        "browserFingerPrint": {},
-        "url": "",
-        "memorySaver": true,
-        "syncTabs": false
-    });
-
-    // 设置分组
-    if let Some(group_id) = config["groupId"].as_str() {
-        params["groupId"] = serde_json::json!(group_id);
-    }
+    let mut profile = BrowserProfile {
+        name: Some(browser_name),
+        remark: Some(config["remark"].as_str().unwrap_or("").to_string()),
+        // 设置分组
+        group_id: config["groupId"].as_str().map(|s| s.to_string()),
+        ..Default::default()
+    };
+    profile.extra.insert("browserFingerPrint".to_string(), serde_json::json!({}));
+    profile.extra.insert("url".to_string(), serde_json::json!(""));
+    profile.extra.insert("memorySaver".to_string(), serde_json::json!(true));
+    profile.extra.insert("syncTabs".to_string(), serde_json::json!(false));
 
     // 解析cookie字符串为cookie数组
-    let cookie_array: Vec<serde_json::Value> = cookie
-        .split("; ")
-        .filter_map(|pair| {
-            let mut parts = pair.splitn(2, '=');
-            let name = parts.next()?;
-            let value = parts.next()?;
-            Some(serde_json::json!({
-                "name": name,
-                "value": value,
-                "domain": ".weixin.qq.com"
-            }))
-        })
-        .collect();
-
-    params["cookie"] = serde_json::json!(cookie_array);
+    profile.set_cookies(&BrowserCookie::parse_cookie_string(&cookie, ".weixin.qq.com"));
 
     // 配置代理
-    if config["proxy"].is_object() {
-        // TODO: 处理代理配置
-        params["proxyMethod"] = serde_json::json!(2);
-        params["proxyType"] = serde_json::json!("noproxy");
-    } else {
-        params["proxyMethod"] = serde_json::json!(2);
-        params["proxyType"] = serde_json::json!("noproxy");
-    }
+    // TODO: 处理 config["proxy"] 代理配置
+    profile.extra.insert("proxyMethod".to_string(), serde_json::json!(2));
+    profile.extra.insert("proxyType".to_string(), serde_json::json!("noproxy"));
 
     // 注意：插件通过 open_browser 的 --load-extension 参数动态加载，无需在创建时配置
 
-    let base_url = get_bb_api_url().await?;
-    let response = client
-        .post(format!("{}/browser/update", base_url))
-        .json(&params)
-        .send()
-        .await
-        .map_err(|e| format!("创建浏览器失败: {}", e))?;
-
-    let result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("解析响应失败: {}", e))?;
-
-    if result["success"].as_bool().unwrap_or(false) {
-        let browser_id = result["data"]["id"].as_str().unwrap_or("");
-        Ok(serde_json::json!({
+    match client.update_browser(&profile).await {
+        Ok(created) => Ok(serde_json::json!({
             "success": true,
-            "browserId": browser_id
-        }))
-    } else {
-        let msg = result["msg"].as_str().unwrap_or("创建失败");
-        Ok(serde_json::json!({
+            "browserId": created.id
+        })),
+        Err(e) if e.is_rejected() => Ok(serde_json::json!({
             "success": false,
-            "message": msg
-        }))
+            "message": e.to_string()
+        })),
+        Err(e) => Err(format!("创建浏览器失败: {}", e)),
     }
 }
 
 // 同步Cookie到浏览器（用于链接登录）
 #[tauri::command]
 async fn sync_cookie_to_browser(browser_id: String, cookie: String) -> Result<ApiResponse, String> {
-    let client = create_http_client();
+    let client = BitBrowserClient::connect().await?;
 
     println!("[同步Cookie] 浏览器ID: {}", browser_id);
 
-    let base_url = get_bb_api_url().await?;
+    // 解析并更新 Cookie（只更新 Cookie，不修改其他配置）
+    let cookies = BrowserCookie::parse_cookie_string(&cookie, ".weixin.qq.com");
+    let patch = ProfilePatch::new().cookies(&cookies);
 
-    // 1. 获取浏览器详情
-    let detail_response = client
-        .post(format!("{}/browser/detail", base_url))
-        .json(&serde_json::json!({
-            "id": browser_id
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("获取浏览器详情失败: {}", e))?;
-
-    if !detail_response.status().is_success() {
-        return Ok(ApiResponse {
-            success: false,
-            message: "获取浏览器详情失败".to_string(),
-            data: None,
-        });
-    }
-
-    let browser_detail: serde_json::Value = detail_response
-        .json()
-        .await
-        .map_err(|e| format!("解析响应失败: {}", e))?;
-
-    if !browser_detail["success"].as_bool().unwrap_or(false) {
-        return Ok(ApiResponse {
-            success: false,
-            message: browser_detail["msg"]
-                .as_str()
-                .unwrap_or("获取浏览器详情失败")
-                .to_string(),
-            data: None,
-        });
-    }
-
-    // 2. 提取现有配置
-    let mut update_params = browser_detail["data"].clone();
-
-    // 3. 解析并更新 Cookie（只更新 Cookie，不修改其他配置）
-    let cookie_array: Vec<serde_json::Value> = cookie
-        .split("; ")
-        .filter_map(|pair| {
-            let mut parts = pair.splitn(2, '=');
-            let name = parts.next()?;
-            let value = parts.next()?;
-            Some(serde_json::json!({
-                "name": name,
-                "value": value,
-                "domain": ".weixin.qq.com"
-            }))
-        })
-        .collect();
-
-    update_params["cookie"] = serde_json::json!(cookie_array);
-
-    println!(
-        "[同步Cookie] 准备更新浏览器，Cookie数量: {}",
-        cookie_array.len()
-    );
-
-    // 5. 调用 BitBrowser API 更新
-    let response = client
-        .post(format!("{}/browser/update", base_url))
-        .json(&update_params)
-        .send()
-        .await
-        .map_err(|e| format!("同步Cookie失败: {}", e))?;
-
-    if !response.status().is_success() {
-        return Ok(ApiResponse {
-            success: false,
-            message: "同步Cookie失败".to_string(),
-            data: None,
-        });
-    }
-
-    let result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("解析响应失败: {}", e))?;
+    println!("[同步Cookie] 准备更新浏览器，Cookie数量: {}", cookies.len());
 
-    if result["success"].as_bool().unwrap_or(false) {
-        println!("[同步Cookie] ✅ Cookie同步成功: {}", browser_id);
-        Ok(ApiResponse {
-            success: true,
-            message: "Cookie同步成功".to_string(),
-            data: None,
-        })
-    } else {
-        let msg = result["msg"].as_str().unwrap_or("同步失败");
-        println!("[同步Cookie] ❌ Cookie同步失败: {}", msg);
-        Ok(ApiResponse {
-            success: false,
-            message: msg.to_string(),
-            data: None,
-        })
+    match bitbrowser_profile::patch_browser(&client, &browser_id, &patch).await {
+        Ok(_) => {
+            println!("[同步Cookie] ✅ Cookie同步成功: {}", browser_id);
+            Ok(ApiResponse {
+                success: true,
+                message: "Cookie同步成功".to_string(),
+                data: None,
+            })
+        }
+        Err(e) => {
+            println!("[同步Cookie] ❌ Cookie同步失败: {}", e);
+            bb_failure(e)
+        }
     }
 }
 
@@ -1937,107 +2049,75 @@ async fn sync_cookie_to_browser(browser_id: String, cookie: String) -> Result<Ap
 // 获取分组列表
 #[tauri::command]
 async fn get_group_list() -> Result<ApiResponse, String> {
-    let base_url = get_bb_api_url().await?;
-    let client = create_http_client();
+    let client = BitBrowserClient::connect().await?;
 
-    let response = client
-        .post(format!("{}/group/list", base_url))
-        .json(&serde_json::json!({
-            "page": 0,
-            "pageSize": 100
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API调用失败: {}", e))?;
-
-    if !response.status().is_success() {
-        return Ok(ApiResponse {
-            success: false,
-            message: "获取分组列表失败".to_string(),
-            data: None,
-        });
+    match client.list_groups(&PageRequest::new(0, 100)).await {
+        Ok(data) => Ok(ApiResponse {
+            success: true,
+            message: "获取分组列表成功".to_string(),
+            data: Some(serde_json::to_value(data).map_err(|e| e.to_string())?),
+        }),
+        Err(e) => bb_failure(e),
     }
-
-    let result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("解析响应失败: {}", e))?;
-
-    Ok(ApiResponse {
-        success: result["success"].as_bool().unwrap_or(false),
-        message: "获取分组列表成功".to_string(),
-        data: result.get("data").cloned(),
-    })
 }
 
 // 获取浏览器列表
+//
+// 未指定 page 时遍历所有分页返回全部浏览器；group_id/name/remark 由 BitBrowser 服务端筛选
 #[tauri::command]
 async fn get_browser_list(
     page: Option<i32>,
     page_size: Option<i32>,
     created_name: Option<String>,
+    group_id: Option<String>,
+    name: Option<String>,
+    remark: Option<String>,
 ) -> Result<ApiResponse, String> {
-    let base_url = get_bb_api_url().await?;
-    let client = create_http_client();
+    let client = BitBrowserClient::connect().await?;
 
-    let response = client
-        .post(format!("{}/browser/list", base_url))
-        .json(&serde_json::json!({
-            "page": page.unwrap_or(0),
-            "pageSize": page_size.unwrap_or(100)
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API调用失败: {}", e))?;
+    let filter = BrowserListRequest {
+        group_id,
+        name,
+        remark,
+        ..Default::default()
+    };
 
-    if !response.status().is_success() {
-        return Ok(ApiResponse {
-            success: false,
-            message: "获取浏览器列表失败".to_string(),
-            data: None,
-        });
-    }
+    let result = match page {
+        Some(page) => {
+            let request = BrowserListRequest {
+                page: page as i64,
+                page_size: page_size.map(|s| s as i64).unwrap_or(MAX_PAGE_SIZE),
+                ..filter
+            };
+            client.list_browsers(&request).await
+        }
+        None => client.browser_pages(filter).collect_all().await,
+    };
 
-    let mut result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("解析响应失败: {}", e))?;
+    let mut data = match result {
+        Ok(data) => data,
+        Err(e) => return bb_failure(e),
+    };
 
     // 如果指定了 created_name，在后端进行筛选
+    let filtered = created_name.is_some();
     if let Some(filter_name) = created_name {
-        if let Some(data) = result.get_mut("data") {
-            // 先筛选浏览器列表
-            let filtered_count = if let Some(list) = data.get_mut("list") {
-                if let Some(browsers) = list.as_array_mut() {
-                    // 筛选出 createdName 匹配的浏览器
-                    browsers.retain(|browser| {
-                        browser
-                            .get("createdName")
-                            .and_then(|v| v.as_str())
-                            .map(|name| name == filter_name)
-                            .unwrap_or(false)
-                    });
-                    Some(browsers.len())
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
+        // 筛选出 createdName 匹配的浏览器
+        data.list
+            .retain(|browser| browser.created_name.as_deref() == Some(filter_name.as_str()));
+    }
 
-            // 更新 total 计数
-            if let Some(count) = filtered_count {
-                if let Some(total_field) = data.as_object_mut() {
-                    total_field.insert("total".to_string(), serde_json::json!(count));
-                }
-            }
-        }
+    let mut value = serde_json::to_value(&data).map_err(|e| e.to_string())?;
+
+    // 更新 total 计数
+    if filtered {
+        value["total"] = serde_json::json!(data.list.len());
     }
 
     Ok(ApiResponse {
-        success: result["success"].as_bool().unwrap_or(false),
+        success: true,
         message: "获取浏览器列表成功".to_string(),
-        data: result.get("data").cloned(),
+        data: Some(value),
     })
 }
 
@@ -2050,19 +2130,24 @@ async fn open_browser(
     clear_cookies: Option<bool>,
     app: tauri::AppHandle,
 ) -> Result<ApiResponse, String> {
-    let base_url = get_bb_api_url().await?;
-    let client = create_http_client();
-
-    let mut payload = serde_json::json!({
-        "id": browser_id,
-        "loadExtensions": true  // 加载扩展中心已启用的扩展
-    });
+    let client = BitBrowserClient::connect().await?;
 
-    // 构建 args 数组：添加扩展路径 + 启动 URL
-    let mut args_vec = args.unwrap_or(vec![]);
+    // 按浏览器内核版本查兼容表（读取配置失败时按最新内核处理）
+    let core_version = match client.browser_detail(&browser_id).await {
+        Ok(profile) => bitbrowser_version::profile_core_version(&profile),
+        Err(e) => {
+            println!("[open_browser] 读取浏览器配置失败: {}, 按最新内核处理", e);
+            None
+        }
+    };
+    let compat = bitbrowser_version::compatibility_for(core_version);
+    for (id, description) in &compat.applied_rules {
+        println!("[open_browser] 兼容规则 {}: {}", id, description);
+    }
 
-    // 🔧 Chrome v137+ 需要添加此参数以重新启用 --load-extension 功能
-    args_vec.push("--disable-features=DisableLoadExtensionCommandLineSwitch".to_string());
+    // 构建 args 数组：兼容参数 + 扩展路径 + 启动 URL
+    let mut args_vec = args.unwrap_or_default();
+    args_vec.extend(compat.launch_args.iter().cloned());
 
     // 🎯 添加扩展加载参数（使用 --load-extension）
     match get_plugin_path(app) {
@@ -2081,265 +2166,105 @@ async fn open_browser(
         args_vec.push(url);
     }
 
-    // 只有在 args 不为空时才添加到 payload
-    if !args_vec.is_empty() {
-        payload["args"] = serde_json::json!(args_vec);
-    }
-
-    if let Some(clear) = clear_cookies {
-        payload["clearCacheFilesBeforeLaunch"] = serde_json::json!(clear);
-    }
+    let request = OpenBrowserRequest {
+        id: browser_id,
+        // 只有在 args 不为空时才添加到请求
+        args: if args_vec.is_empty() { None } else { Some(args_vec) },
+        load_extensions: compat.api_flag("loadExtensions"),
+        clear_cache_files_before_launch: clear_cookies,
+    };
 
     println!(
         "[open_browser] 请求payload: {}",
-        serde_json::to_string_pretty(&payload).unwrap_or_default()
+        serde_json::to_string_pretty(&request).unwrap_or_default()
     );
 
-    let response = client
-        .post(format!("{}/browser/open", base_url))
-        .json(&payload)
-        .send()
-        .await
-        .map_err(|e| format!("API调用失败: {}", e))?;
-
-    if !response.status().is_success() {
-        return Ok(ApiResponse {
-            success: false,
-            message: "打开浏览器失败".to_string(),
-            data: None,
-        });
+    match client.open_browser(&request).await {
+        Ok(data) => Ok(ApiResponse {
+            success: true,
+            message: "浏览器已启动".to_string(),
+            data: Some(serde_json::to_value(data).map_err(|e| e.to_string())?),
+        }),
+        Err(e) => bb_failure(e),
     }
-
-    let result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("解析响应失败: {}", e))?;
-
-    Ok(ApiResponse {
-        success: result["success"].as_bool().unwrap_or(false),
-        message: "浏览器已启动".to_string(),
-        data: result.get("data").cloned(),
-    })
 }
 
 // 关闭浏览器
 #[tauri::command]
 async fn close_browser(browser_id: String) -> Result<ApiResponse, String> {
-    let base_url = get_bb_api_url().await?;
-    let client = create_http_client();
-
-    let response = client
-        .post(format!("{}/browser/close", base_url))
-        .json(&serde_json::json!({
-            "id": browser_id
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API调用失败: {}", e))?;
+    let client = BitBrowserClient::connect().await?;
 
-    if !response.status().is_success() {
-        return Ok(ApiResponse {
-            success: false,
-            message: "关闭浏览器失败".to_string(),
+    match client.close_browser(&browser_id).await {
+        Ok(_) => Ok(ApiResponse {
+            success: true,
+            message: "浏览器已关闭".to_string(),
             data: None,
-        });
+        }),
+        Err(e) => bb_failure(e),
     }
-
-    let result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("解析响应失败: {}", e))?;
-
-    Ok(ApiResponse {
-        success: result["success"].as_bool().unwrap_or(false),
-        message: "浏览器已关闭".to_string(),
-        data: None,
-    })
 }
 
 // 删除浏览器
 #[tauri::command]
 async fn delete_browser(browser_id: String) -> Result<ApiResponse, String> {
-    let base_url = get_bb_api_url().await?;
-    let client = create_http_client();
-
-    let response = client
-        .post(format!("{}/browser/delete", base_url))
-        .json(&serde_json::json!({
-            "id": browser_id
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API调用失败: {}", e))?;
+    let client = BitBrowserClient::connect().await?;
 
-    if !response.status().is_success() {
-        return Ok(ApiResponse {
-            success: false,
-            message: "删除浏览器失败".to_string(),
+    match client.delete_browser(&browser_id).await {
+        Ok(_) => Ok(ApiResponse {
+            success: true,
+            message: "浏览器已删除".to_string(),
             data: None,
-        });
+        }),
+        Err(e) => bb_failure(e),
     }
-
-    let result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("解析响应失败: {}", e))?;
-
-    Ok(ApiResponse {
-        success: result["success"].as_bool().unwrap_or(false),
-        message: "浏览器已删除".to_string(),
-        data: None,
-    })
 }
 
 // 更新浏览器名称（由 Realtime 服务调用）
 #[tauri::command]
 async fn update_browser_name(browser_id: String, name: String) -> Result<ApiResponse, String> {
-    let base_url = get_bb_api_url().await?;
-    let client = create_http_client();
+    let client = BitBrowserClient::connect().await?;
 
     println!(
         "[更新浏览器名称] 浏览器ID: {}, 新名称: {}",
         browser_id, name
     );
 
-    // 1. 获取浏览器详情
-    let detail_response = client
-        .post(format!("{}/browser/detail", base_url))
-        .json(&serde_json::json!({
-            "id": browser_id
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("获取浏览器详情失败: {}", e))?;
-
-    if !detail_response.status().is_success() {
-        return Ok(ApiResponse {
-            success: false,
-            message: "获取浏览器详情失败".to_string(),
-            data: None,
-        });
-    }
-
-    let browser_detail: serde_json::Value = detail_response
-        .json()
-        .await
-        .map_err(|e| format!("解析响应失败: {}", e))?;
+    // 只更新名称
+    let patch = ProfilePatch::new().name(&name);
 
-    if !browser_detail["success"].as_bool().unwrap_or(false) {
-        return Ok(ApiResponse {
-            success: false,
-            message: browser_detail["msg"]
-                .as_str()
-                .unwrap_or("获取浏览器详情失败")
-                .to_string(),
-            data: None,
-        });
-    }
-
-    // 2. 只更新名称
-    let mut update_params = browser_detail["data"].clone();
-    update_params["name"] = serde_json::json!(name);
-
-    // 3. 调用 BitBrowser API 更新
-    let response = client
-        .post(format!("{}/browser/update", base_url))
-        .json(&update_params)
-        .send()
-        .await
-        .map_err(|e| format!("更新浏览器名称失败: {}", e))?;
-
-    if !response.status().is_success() {
-        return Ok(ApiResponse {
-            success: false,
-            message: "更新浏览器名称失败".to_string(),
-            data: None,
-        });
-    }
-
-    let result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("解析响应失败: {}", e))?;
-
-    if result["success"].as_bool().unwrap_or(false) {
-        println!(
-            "[更新浏览器名称] ✅ 名称更新成功: {} -> {}",
-            browser_id, name
-        );
-        Ok(ApiResponse {
-            success: true,
-            message: "浏览器名称已更新".to_string(),
-            data: None,
-        })
-    } else {
-        let msg = result["msg"].as_str().unwrap_or("更新失败");
-        println!("[更新浏览器名称] ❌ 名称更新失败: {}", msg);
-        Ok(ApiResponse {
-            success: false,
-            message: msg.to_string(),
-            data: None,
-        })
+    match bitbrowser_profile::patch_browser(&client, &browser_id, &patch).await {
+        Ok(_) => {
+            println!(
+                "[更新浏览器名称] ✅ 名称更新成功: {} -> {}",
+                browser_id, name
+            );
+            Ok(ApiResponse {
+                success: true,
+                message: "浏览器名称已更新".to_string(),
+                data: None,
+            })
+        }
+        Err(e) => {
+            println!("[更新浏览器名称] ❌ 名称更新失败: {}", e);
+            bb_failure(e)
+        }
     }
 }
 
 // 获取浏览器Cookie
 #[tauri::command]
 async fn get_browser_cookies(browser_id: String) -> Result<ApiResponse, String> {
-    let base_url = get_bb_api_url().await?;
-    let client = create_http_client();
+    let client = BitBrowserClient::connect().await?;
 
     // 调用 browser/detail 获取浏览器详情
-    let response = client
-        .post(format!("{}/browser/detail", base_url))
-        .json(&serde_json::json!({
-            "id": browser_id
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API调用失败: {}", e))?;
-
-    if !response.status().is_success() {
-        return Ok(ApiResponse {
-            success: false,
-            message: "获取浏览器详情失败".to_string(),
-            data: None,
-        });
-    }
-
-    let result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("解析响应失败: {}", e))?;
-
-    if !result["success"].as_bool().unwrap_or(false) {
-        return Ok(ApiResponse {
-            success: false,
-            message: result["msg"]
-                .as_str()
-                .unwrap_or("获取浏览器详情失败")
-                .to_string(),
-            data: None,
-        });
-    }
-
-    // 提取 cookie 字段（这是一个 JSON 字符串，不是数组）
-    let cookie_str = result["data"]["cookie"].as_str().unwrap_or("");
-
-    // 如果 cookie 字段为空或空字符串，返回空数组
-    let cookies = if cookie_str.is_empty() {
-        serde_json::Value::Array(vec![])
-    } else {
-        // 解析 JSON 字符串为数组
-        match serde_json::from_str::<serde_json::Value>(cookie_str) {
-            Ok(parsed) => parsed,
-            Err(e) => {
-                println!("[get_browser_cookies] 解析Cookie JSON失败: {}", e);
-                serde_json::Value::Array(vec![])
-            }
-        }
+    let profile = match client.browser_detail(&browser_id).await {
+        Ok(profile) => profile,
+        Err(e) => return bb_failure(e),
     };
 
+    // 解析 cookie 字段（BitBrowser 返回 JSON 字符串，为空时返回空数组）
+    let cookies = profile.cookies()?;
+
     Ok(ApiResponse {
         success: true,
         message: "获取Cookie成功".to_string(),
@@ -2485,85 +2410,78 @@ fn get_plugin_path(
 // 获取浏览器详情
 #[tauri::command]
 async fn get_browser_detail(browser_id: String) -> Result<ApiResponse, String> {
-    let base_url = get_bb_api_url().await?;
-    let client = create_http_client();
-
-    let response = client
-        .post(format!("{}/browser/detail", base_url))
-        .json(&serde_json::json!({
-            "id": browser_id
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API调用失败: {}", e))?;
+    let client = BitBrowserClient::connect().await?;
 
-    if !response.status().is_success() {
-        return Ok(ApiResponse {
-            success: false,
-            message: "获取浏览器详情失败".to_string(),
-            data: None,
-        });
+    match client.browser_detail(&browser_id).await {
+        Ok(profile) => Ok(ApiResponse {
+            success: true,
+            message: String::new(),
+            data: Some(serde_json::to_value(profile).map_err(|e| e.to_string())?),
+        }),
+        Err(e) => bb_failure(e),
     }
-
-    let result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("解析响应失败: {}", e))?;
-
-    Ok(ApiResponse {
-        success: result["success"].as_bool().unwrap_or(false),
-        message: result["msg"].as_str().unwrap_or("").to_string(),
-        data: result.get("data").cloned(),
-    })
 }
 
-// 更新浏览器配置
+// 更新浏览器配置（只修改 config 中提供的字段）
 #[tauri::command]
 async fn update_browser(browser_id: String, config: serde_json::Value) -> Result<ApiResponse, String> {
-    let base_url = get_bb_api_url().await?;
-    let client = create_http_client();
+    let client = BitBrowserClient::connect().await?;
 
-    let mut update_params = config;
-    update_params["id"] = serde_json::json!(browser_id);
+    let patch = ProfilePatch::from_object(config)?;
 
-    // 🔍 调试：打印发送的配置
+    // 🔍 调试：打印修改的字段（不输出字段值，避免 Cookie 等敏感数据进入日志）
     println!("[update_browser] 发送的配置:");
     println!("  browser_id: {}", browser_id);
-    println!("  extensions: {:?}", update_params.get("extensions"));
+    println!("  fields: {:?}", patch.field_names());
 
-    let response = client
-        .post(format!("{}/browser/update", base_url))
-        .json(&update_params)
-        .send()
-        .await
-        .map_err(|e| format!("API调用失败: {}", e))?;
+    match bitbrowser_profile::patch_browser(&client, &browser_id, &patch).await {
+        Ok(updated) => {
+            // 🔍 调试：打印返回结果
+            println!("[update_browser] BitBrowser 返回:");
+            println!("  data.extensions: {:?}", updated.extra.get("extensions"));
 
-    if !response.status().is_success() {
-        return Ok(ApiResponse {
-            success: false,
-            message: "更新浏览器配置失败".to_string(),
-            data: None,
-        });
+            Ok(ApiResponse {
+                success: true,
+                message: String::new(),
+                data: Some(serde_json::to_value(updated).map_err(|e| e.to_string())?),
+            })
+        }
+        Err(e) => {
+            println!("[update_browser] BitBrowser 返回错误: {}", e);
+            bb_failure(e)
+        }
     }
+}
 
-    let result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("解析响应失败: {}", e))?;
-
-    // 🔍 调试：打印返回结果
-    println!("[update_browser] BitBrowser 返回:");
-    println!("  success: {}", result["success"].as_bool().unwrap_or(false));
-    println!("  message: {}", result["msg"].as_str().unwrap_or(""));
-    if let Some(data) = result.get("data") {
-        println!("  data.extensions: {:?}", data.get("extensions"));
-    }
+/// 订阅配置变更：API 地址、Token、启动配置和自动恢复策略修改后无需重启即可生效
+fn watch_config(config: &config_manager::ConfigManager, state: &AppState) -> Result<(), String> {
+    let as_string = |value: &serde_json::Value| value.as_str().map(|s| s.to_string());
+
+    // API 地址（未配置时自动探测端口）和 Token 变更后立即重新检测连接
+    let monitor = state.monitor.state().clone();
+    config.subscribe("bitbrowser_api", move |value| {
+        bitbrowser_manager::set_configured_api_url(as_string(value));
+        monitor.request_recheck();
+    })?;
+
+    let monitor = state.monitor.state().clone();
+    config.subscribe("bitbrowser_api_token", move |value| {
+        bitbrowser_client::set_api_token(as_string(value));
+        monitor.request_recheck();
+    })?;
+
+    // 下次启动 BitBrowser 时生效
+    config.subscribe("bitbrowser_launch", |value| {
+        bitbrowser_launch_profile::set_launch_profile(serde_json::from_value(value.clone()).ok());
+    })?;
+
+    // 自动恢复策略（未配置时关闭）
+    let monitor = state.monitor.state().clone();
+    config.subscribe("bitbrowser_recovery", move |value| {
+        monitor.set_recovery_policy(serde_json::from_value(value.clone()).unwrap_or_default());
+    })?;
 
-    Ok(ApiResponse {
-        success: result["success"].as_bool().unwrap_or(false),
-        message: result["msg"].as_str().unwrap_or("").to_string(),
-        data: result.get("data").cloned(),
-    })
+    Ok(())
 }
 
 fn main() {
@@ -2573,7 +2491,10 @@ fn main() {
             browser_list: Mutex::new(Vec::new()),
             checking_cookies: Mutex::new(HashSet::new()),
             bitbrowser_connected: Mutex::new(false),
-            monitor_running: Arc::new(AtomicBool::new(true)),
+            monitor: bitbrowser_monitor::MonitorController::new(Arc::new(
+                bitbrowser_monitor::MonitorState::default(),
+            )),
+            search: bitbrowser_search::SearchController::new(),
         })
         // 初始化登录状态
         .manage(LoginState {
@@ -2587,13 +2508,17 @@ fn main() {
         // 启动后台监控任务
         .setup(|app| {
             let app_handle = app.handle();
-            let state = app.state::<AppState>();
-            let monitor_running = state.monitor_running.clone();
+
+            // 配置变更推送到前端，并让相关模块立即应用新值
+            let config = app.state::<config_manager::ConfigManager>();
+            config.attach_app_handle(app_handle.clone());
+            config.emit_load_warning();
+            watch_config(&config, &app.state::<AppState>())?;
+            config_manager::start_file_watcher(app_handle.clone());
 
             // 启动后台监控任务
-            tauri::async_runtime::spawn(async move {
-                bitbrowser_monitor::start_monitor(app_handle, monitor_running).await;
-            });
+            let state = app.state::<AppState>();
+            state.monitor.start(app_handle);
 
             Ok(())
         })
@@ -2609,13 +2534,26 @@ fn main() {
             remove_checking_cookie,
             get_bitbrowser_status,
             update_bitbrowser_status,
+            recheck_now,
+            pause_monitor,
+            resume_monitor,
+            get_monitor_status,
+            configure_monitor,
+            get_connection_history,
             // BitBrowser 管理命令
             find_bitbrowser,
+            get_search_roots,
+            start_bitbrowser_search,
+            cancel_bitbrowser_search,
             get_bitbrowser_info,
+            get_bitbrowser_version,
             launch_bitbrowser,
+            launch_bitbrowser_and_wait,
             stop_bitbrowser,
             clear_bitbrowser_cache,
             kill_process_by_pid,
+            inspect_port_conflict,
+            resolve_port_conflict,
             // 微信登录命令
             generate_login_qr,
             check_qr_status,
@@ -2639,6 +2577,15 @@ fn main() {
             config_manager::config_set_string,
             config_manager::config_get_bool,
             config_manager::config_set_bool,
+            config_manager::config_get,
+            config_manager::config_set,
+            config_manager::config_list_schema,
+            config_manager::config_get_load_report,
+            config_manager::config_get_launch_profile,
+            config_manager::config_set_launch_profile,
+            config_manager::config_get_secrets_status,
+            config_manager::config_unlock_secrets,
+            config_manager::config_rekey_secrets,
             config_manager::config_get_all_accounts,
             config_manager::config_get_account,
             config_manager::config_save_account,
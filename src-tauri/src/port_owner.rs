@@ -0,0 +1,330 @@
+/**
+ * Port Owner
+ * 查询监听指定 TCP 端口的进程 PID
+ *
+ * 实现：
+ * - Linux：解析 /proc/net/tcp、/proc/net/tcp6，再通过 /proc/<pid>/fd 将 socket inode 映射到 PID
+ * - Windows：调用 IP Helper 的 GetExtendedTcpTable
+ * - 其他平台或原生查询失败时：回退到 netstat / lsof
+ */
+use std::process::Command;
+
+/// 查找监听指定端口的进程 PID
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+pub fn find_process_using_port(port: u16) -> Option<u32> {
+    match native_lookup(port) {
+        Ok(pid) => pid,
+        Err(e) => {
+            println!("⚠ 原生端口查询失败（{}），改用命令行工具", e);
+            command_lookup(port)
+        }
+    }
+}
+
+/// 查找监听指定端口的进程 PID
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn find_process_using_port(port: u16) -> Option<u32> {
+    command_lookup(port)
+}
+
+// ==================== Linux：/proc ====================
+
+#[cfg(target_os = "linux")]
+fn native_lookup(port: u16) -> Result<Option<u32>, String> {
+    linux::find_port_owner_in(std::path::Path::new("/proc"), port)
+}
+
+#[cfg(any(target_os = "linux", test))]
+mod linux {
+    use std::fs;
+    use std::path::Path;
+
+    /// TCP_LISTEN 状态
+    const TCP_LISTEN: &str = "0A";
+
+    /// 从 /proc/net/tcp(6) 的内容中找出监听指定端口的 socket inode
+    pub fn listening_inodes(table: &str, port: u16) -> Vec<u64> {
+        table
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                // sl local_address rem_address st tx:rx tr:when retrnsmt uid timeout inode
+                if fields.len() < 10 || fields[3] != TCP_LISTEN {
+                    return None;
+                }
+
+                let (_, local_port) = fields[1].rsplit_once(':')?;
+                if u16::from_str_radix(local_port, 16).ok()? != port {
+                    return None;
+                }
+
+                fields[9].parse::<u64>().ok().filter(|inode| *inode != 0)
+            })
+            .collect()
+    }
+
+    /// 遍历 /proc/<pid>/fd，找到持有指定 socket inode 的进程
+    ///
+    /// 无权限读取的进程直接跳过
+    pub fn find_pid_by_inodes(proc_root: &Path, inodes: &[u64]) -> Option<u32> {
+        let targets: Vec<String> = inodes.iter().map(|i| format!("socket:[{}]", i)).collect();
+
+        for entry in fs::read_dir(proc_root).ok()?.flatten() {
+            let pid = match entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) {
+                Some(pid) => pid,
+                None => continue,
+            };
+
+            let fds = match fs::read_dir(entry.path().join("fd")) {
+                Ok(fds) => fds,
+                Err(_) => continue,
+            };
+
+            for fd in fds.flatten() {
+                if let Ok(link) = fs::read_link(fd.path()) {
+                    if targets.iter().any(|t| link.as_os_str() == t.as_str()) {
+                        return Some(pid);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// 在指定的 proc 根目录下查找监听端口的进程
+    pub fn find_port_owner_in(proc_root: &Path, port: u16) -> Result<Option<u32>, String> {
+        let tcp = fs::read_to_string(proc_root.join("net/tcp"))
+            .map_err(|e| format!("读取 net/tcp 失败: {}", e))?;
+        let mut inodes = listening_inodes(&tcp, port);
+
+        // 未启用 IPv6 时没有 tcp6
+        if let Ok(tcp6) = fs::read_to_string(proc_root.join("net/tcp6")) {
+            inodes.extend(listening_inodes(&tcp6, port));
+        }
+
+        if inodes.is_empty() {
+            return Ok(None);
+        }
+
+        match find_pid_by_inodes(proc_root, &inodes) {
+            Some(pid) => Ok(Some(pid)),
+            None => Err(format!("端口 {} 正在被监听，但无权限读取其所属进程", port)),
+        }
+    }
+}
+
+// ==================== Windows：IP Helper ====================
+
+#[cfg(target_os = "windows")]
+fn native_lookup(port: u16) -> Result<Option<u32>, String> {
+    use iphlpapi::*;
+
+    let ipv4 = read_tcp_table(AF_INET)?;
+    if let Some(pid) = find_in_tcp_table(&ipv4, &IPV4_LAYOUT, port) {
+        return Ok(Some(pid));
+    }
+
+    let ipv6 = read_tcp_table(AF_INET6)?;
+    Ok(find_in_tcp_table(&ipv6, &IPV6_LAYOUT, port))
+}
+
+/// MIB_TCPROW_OWNER_PID / MIB_TCP6ROW_OWNER_PID 的字段布局
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+struct TcpRowLayout {
+    row_size: usize,
+    port_offset: usize,
+    pid_offset: usize,
+}
+
+/// MIB_TCPROW_OWNER_PID：state, localAddr, localPort, remoteAddr, remotePort, pid
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const IPV4_LAYOUT: TcpRowLayout = TcpRowLayout {
+    row_size: 24,
+    port_offset: 8,
+    pid_offset: 20,
+};
+
+/// MIB_TCP6ROW_OWNER_PID：localAddr[16], localScopeId, localPort, remoteAddr[16],
+/// remoteScopeId, remotePort, state, pid
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const IPV6_LAYOUT: TcpRowLayout = TcpRowLayout {
+    row_size: 56,
+    port_offset: 20,
+    pid_offset: 52,
+};
+
+/// 在 GetExtendedTcpTable 返回的缓冲区中查找监听指定端口的 PID
+///
+/// 缓冲区格式：dwNumEntries (u32) 后紧跟各行；端口以网络字节序存放在低 16 位
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn find_in_tcp_table(buf: &[u8], layout: &TcpRowLayout, port: u16) -> Option<u32> {
+    let read_u32 = |offset: usize| -> Option<u32> {
+        let bytes = buf.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+
+    let count = read_u32(0)? as usize;
+
+    (0..count).find_map(|i| {
+        let row = 4 + i * layout.row_size;
+        let port_bytes = buf.get(row + layout.port_offset..row + layout.port_offset + 2)?;
+        if u16::from_be_bytes([port_bytes[0], port_bytes[1]]) != port {
+            return None;
+        }
+        read_u32(row + layout.pid_offset)
+    })
+}
+
+#[cfg(target_os = "windows")]
+mod iphlpapi {
+    pub const AF_INET: u32 = 2;
+    pub const AF_INET6: u32 = 23;
+    const TCP_TABLE_OWNER_PID_LISTENER: i32 = 3;
+    const NO_ERROR: u32 = 0;
+    const ERROR_INSUFFICIENT_BUFFER: u32 = 122;
+
+    #[link(name = "iphlpapi")]
+    extern "system" {
+        fn GetExtendedTcpTable(
+            table: *mut u8,
+            size: *mut u32,
+            order: i32,
+            af: u32,
+            table_class: i32,
+            reserved: u32,
+        ) -> u32;
+    }
+
+    /// 读取处于监听状态的 TCP 连接表（含 PID）
+    pub fn read_tcp_table(af: u32) -> Result<Vec<u8>, String> {
+        let mut size: u32 = 0;
+        let mut buf: Vec<u8> = Vec::new();
+
+        // 表在两次调用之间可能变大，缓冲区不足时重试
+        for _ in 0..3 {
+            buf.resize(size as usize, 0);
+            let ptr = if buf.is_empty() { std::ptr::null_mut() } else { buf.as_mut_ptr() };
+            let result = unsafe {
+                GetExtendedTcpTable(ptr, &mut size, 0, af, TCP_TABLE_OWNER_PID_LISTENER, 0)
+            };
+
+            match result {
+                NO_ERROR => return Ok(buf),
+                ERROR_INSUFFICIENT_BUFFER => continue,
+                code => return Err(format!("GetExtendedTcpTable 返回错误码 {}", code)),
+            }
+        }
+
+        Err("GetExtendedTcpTable 缓冲区大小不稳定".to_string())
+    }
+}
+
+// ==================== 命令行回退 ====================
+
+#[cfg(target_os = "windows")]
+fn command_lookup(port: u16) -> Option<u32> {
+    // 使用 netstat 查找占用指定端口的进程
+    let output = Command::new("netstat")
+        .args(["-ano"])
+        .output()
+        .ok()?;
+
+    let output_str = String::from_utf8_lossy(&output.stdout);
+
+    // 查找包含指定端口的行
+    for line in output_str.lines() {
+        if line.contains(&format!(":{}", port)) && line.contains("LISTENING") {
+            // 提取 PID（最后一列）
+            if let Some(pid_str) = line.split_whitespace().last() {
+                if let Ok(pid) = pid_str.parse::<u32>() {
+                    return Some(pid);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(not(target_os = "windows"))]
+fn command_lookup(port: u16) -> Option<u32> {
+    // 使用 lsof 查找占用指定端口的进程
+    let output = Command::new("lsof")
+        .args(["-i", &format!(":{}", port), "-t"])
+        .output()
+        .ok()?;
+
+    let output_str = String::from_utf8_lossy(&output.stdout);
+    if let Some(first_line) = output_str.lines().next() {
+        if let Ok(pid) = first_line.trim().parse::<u32>() {
+            return Some(pid);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROC_NET_TCP: &str = include_str!("../fixtures/proc/net/tcp");
+    const PROC_NET_TCP6: &str = include_str!("../fixtures/proc/net/tcp6");
+
+    #[test]
+    fn test_listening_inodes() {
+        // 只返回 LISTEN 状态的行（同端口的已建立连接不算）
+        assert_eq!(linux::listening_inodes(PROC_NET_TCP, 54345), vec![52311]);
+        assert_eq!(linux::listening_inodes(PROC_NET_TCP, 53), vec![18034]);
+        assert!(linux::listening_inodes(PROC_NET_TCP, 40026).is_empty());
+        assert_eq!(linux::listening_inodes(PROC_NET_TCP6, 54346), vec![61877]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_port_owner_in_fixture_proc() {
+        use std::os::unix::fs::symlink;
+
+        let root = std::env::temp_dir().join(format!("port-owner-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("net")).unwrap();
+        std::fs::write(root.join("net/tcp"), PROC_NET_TCP).unwrap();
+        std::fs::write(root.join("net/tcp6"), PROC_NET_TCP6).unwrap();
+
+        for (pid, inode) in [(101, 18034), (4242, 52311), (5151, 61877)] {
+            let fd_dir = root.join(format!("{}/fd", pid));
+            std::fs::create_dir_all(&fd_dir).unwrap();
+            symlink("/dev/null", fd_dir.join("0")).unwrap();
+            symlink(format!("socket:[{}]", inode), fd_dir.join("3")).unwrap();
+        }
+
+        assert_eq!(linux::find_port_owner_in(&root, 54345), Ok(Some(4242)));
+        assert_eq!(linux::find_port_owner_in(&root, 54346), Ok(Some(5151)));
+        assert_eq!(linux::find_port_owner_in(&root, 8080), Ok(None));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_find_in_tcp_table() {
+        // 两行 IPv4：0.0.0.0:135 (PID 900)，127.0.0.1:54345 (PID 7788)
+        let mut buf = 2u32.to_le_bytes().to_vec();
+        for (addr, port, pid) in [([0u8, 0, 0, 0], 135u16, 900u32), ([127, 0, 0, 1], 54345, 7788)] {
+            buf.extend_from_slice(&2u32.to_le_bytes()); // MIB_TCP_STATE_LISTEN
+            buf.extend_from_slice(&addr);
+            buf.extend_from_slice(&port.to_be_bytes());
+            buf.extend_from_slice(&[0, 0]);
+            buf.extend_from_slice(&[0; 8]);
+            buf.extend_from_slice(&pid.to_le_bytes());
+        }
+
+        assert_eq!(find_in_tcp_table(&buf, &IPV4_LAYOUT, 54345), Some(7788));
+        assert_eq!(find_in_tcp_table(&buf, &IPV4_LAYOUT, 135), Some(900));
+        assert_eq!(find_in_tcp_table(&buf, &IPV4_LAYOUT, 80), None);
+
+        // 截断的缓冲区不会越界
+        assert_eq!(find_in_tcp_table(&buf[..30], &IPV4_LAYOUT, 54345), None);
+    }
+}
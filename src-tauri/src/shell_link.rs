@@ -0,0 +1,444 @@
+/**
+ * Shell Link
+ * 解析 Windows 快捷方式（.lnk，MS-SHLLINK 格式）的目标路径
+ *
+ * 支持：
+ * - LinkInfo 中的本地路径（ANSI / Unicode）和网络路径
+ * - StringData（相对路径、工作目录、参数、图标）
+ * - EnvironmentVariableDataBlock 中带环境变量的目标路径
+ */
+use std::path::Path;
+
+/// ShellLinkHeader 固定长度
+const HEADER_SIZE: usize = 0x4C;
+
+/// LinkCLSID：00021401-0000-0000-C000-000000000046
+const LINK_CLSID: [u8; 16] = [
+    0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+];
+
+// LinkFlags
+const HAS_LINK_TARGET_ID_LIST: u32 = 0x0000_0001;
+const HAS_LINK_INFO: u32 = 0x0000_0002;
+const HAS_NAME: u32 = 0x0000_0004;
+const HAS_RELATIVE_PATH: u32 = 0x0000_0008;
+const HAS_WORKING_DIR: u32 = 0x0000_0010;
+const HAS_ARGUMENTS: u32 = 0x0000_0020;
+const HAS_ICON_LOCATION: u32 = 0x0000_0040;
+const IS_UNICODE: u32 = 0x0000_0080;
+
+// LinkInfoFlags
+const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x1;
+const COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX: u32 = 0x2;
+
+/// EnvironmentVariableDataBlock 签名
+const ENVIRONMENT_BLOCK_SIGNATURE: u32 = 0xA000_0001;
+
+/// 快捷方式中与目标相关的信息
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ShellLink {
+    /// LinkInfo 中的本地或网络目标路径
+    pub link_info_target: Option<String>,
+    pub name: Option<String>,
+    pub relative_path: Option<String>,
+    pub working_dir: Option<String>,
+    pub arguments: Option<String>,
+    pub icon_location: Option<String>,
+    /// 带环境变量的目标路径（例如 %LOCALAPPDATA%\...）
+    pub environment_target: Option<String>,
+}
+
+impl ShellLink {
+    /// 解析 .lnk 文件内容
+    pub fn parse(data: &[u8]) -> Result<ShellLink, String> {
+        let mut reader = Reader { data, pos: 0 };
+
+        // 1. ShellLinkHeader
+        if reader.u32_at(0)? as usize != HEADER_SIZE || data.get(4..20) != Some(&LINK_CLSID[..]) {
+            return Err("不是有效的快捷方式文件".to_string());
+        }
+        let flags = reader.u32_at(20)?;
+        reader.pos = HEADER_SIZE;
+
+        let mut link = ShellLink::default();
+
+        // 2. LinkTargetIDList（Shell 项 ID 列表，直接跳过）
+        if flags & HAS_LINK_TARGET_ID_LIST != 0 {
+            let size = reader.u16_at(reader.pos)? as usize;
+            reader.skip(2 + size)?;
+        }
+
+        // 3. LinkInfo
+        if flags & HAS_LINK_INFO != 0 {
+            let start = reader.pos;
+            let size = reader.u32_at(start)? as usize;
+            let info = data
+                .get(start..start + size)
+                .ok_or_else(|| "LinkInfo 长度越界".to_string())?;
+            link.link_info_target = parse_link_info(info)?;
+            reader.skip(size)?;
+        }
+
+        // 4. StringData
+        let unicode = flags & IS_UNICODE != 0;
+        let string_fields = [
+            (HAS_NAME, &mut link.name),
+            (HAS_RELATIVE_PATH, &mut link.relative_path),
+            (HAS_WORKING_DIR, &mut link.working_dir),
+            (HAS_ARGUMENTS, &mut link.arguments),
+            (HAS_ICON_LOCATION, &mut link.icon_location),
+        ];
+        for (flag, field) in string_fields {
+            if flags & flag != 0 {
+                *field = Some(reader.counted_string(unicode)?).filter(|s| !s.is_empty());
+            }
+        }
+
+        // 5. ExtraData（读到结束块或数据末尾）
+        while let Ok(size) = reader.u32_at(reader.pos) {
+            let size = size as usize;
+            if size < 8 {
+                break;
+            }
+            let block = match data.get(reader.pos..reader.pos + size) {
+                Some(block) => block,
+                None => break,
+            };
+
+            if read_u32(block, 4) == Some(ENVIRONMENT_BLOCK_SIGNATURE) {
+                // TargetAnsi[260] 之后是 TargetUnicode[520]
+                link.environment_target = block
+                    .get(268..788)
+                    .map(utf16_until_nul)
+                    .filter(|s| !s.is_empty())
+                    .or_else(|| block.get(8..268).map(ansi_until_nul).filter(|s| !s.is_empty()));
+            }
+            reader.pos += size;
+        }
+
+        Ok(link)
+    }
+
+    /// 解析出目标路径（不检查文件是否存在）
+    ///
+    /// 优先级：LinkInfo → 环境变量路径 → 相对快捷方式所在目录的路径
+    pub fn target(&self, lnk_dir: Option<&Path>) -> Option<String> {
+        if let Some(target) = &self.link_info_target {
+            return Some(target.clone());
+        }
+
+        if let Some(target) = &self.environment_target {
+            return Some(expand_environment_variables(target));
+        }
+
+        let relative = self.relative_path.as_ref()?;
+        let joined = match lnk_dir {
+            Some(dir) => dir.join(relative.replace('\\', std::path::MAIN_SEPARATOR_STR)),
+            None => Path::new(relative).to_path_buf(),
+        };
+        Some(joined.to_string_lossy().to_string())
+    }
+}
+
+/// 读取并解析快捷方式文件的目标路径
+pub fn resolve_lnk_target(lnk_path: &Path) -> Option<String> {
+    let data = std::fs::read(lnk_path).ok()?;
+    match ShellLink::parse(&data) {
+        Ok(link) => link.target(lnk_path.parent()),
+        Err(e) => {
+            println!("⚠ 解析快捷方式失败 {:?}: {}", lnk_path, e);
+            None
+        }
+    }
+}
+
+/// 解析 LinkInfo 结构中的目标路径
+fn parse_link_info(info: &[u8]) -> Result<Option<String>, String> {
+    let header_size = read_u32(info, 4).ok_or("LinkInfo 头部不完整")? as usize;
+    let flags = read_u32(info, 8).ok_or("LinkInfo 头部不完整")?;
+    let local_base_offset = read_u32(info, 16).unwrap_or(0) as usize;
+    let network_offset = read_u32(info, 20).unwrap_or(0) as usize;
+    let suffix_offset = read_u32(info, 24).unwrap_or(0) as usize;
+
+    // 头部 >= 0x24 时带有 Unicode 偏移
+    let has_unicode = header_size >= 0x24;
+    let local_base_unicode = if has_unicode { read_u32(info, 28).unwrap_or(0) as usize } else { 0 };
+    let suffix_unicode = if has_unicode { read_u32(info, 32).unwrap_or(0) as usize } else { 0 };
+
+    let suffix = if suffix_unicode > 0 {
+        info.get(suffix_unicode..).map(utf16_until_nul)
+    } else if suffix_offset > 0 {
+        info.get(suffix_offset..).map(ansi_until_nul)
+    } else {
+        None
+    }
+    .unwrap_or_default();
+
+    if flags & VOLUME_ID_AND_LOCAL_BASE_PATH != 0 {
+        let base = if local_base_unicode > 0 {
+            info.get(local_base_unicode..).map(utf16_until_nul)
+        } else {
+            info.get(local_base_offset..).map(ansi_until_nul)
+        };
+        if let Some(base) = base.filter(|b| !b.is_empty()) {
+            return Ok(Some(base + &suffix));
+        }
+    }
+
+    if flags & COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX != 0 {
+        let network = info.get(network_offset..).ok_or("网络路径偏移越界")?;
+        let net_name_offset = read_u32(network, 8).unwrap_or(0) as usize;
+        // NetNameOffset > 0x14 时带有 Unicode 网络名
+        let net_name = if net_name_offset > 0x14 {
+            let unicode_offset = read_u32(network, 20).unwrap_or(0) as usize;
+            network.get(unicode_offset..).map(utf16_until_nul)
+        } else {
+            network.get(net_name_offset..).map(ansi_until_nul)
+        };
+        if let Some(net_name) = net_name.filter(|n| !n.is_empty()) {
+            let target = if suffix.is_empty() {
+                net_name
+            } else {
+                format!("{}\\{}", net_name.trim_end_matches('\\'), suffix)
+            };
+            return Ok(Some(target));
+        }
+    }
+
+    Ok(None)
+}
+
+/// 展开 %VAR% 形式的环境变量（未定义的变量保持原样）
+fn expand_environment_variables(value: &str) -> String {
+    let mut result = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find('%') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('%') {
+            Some(end) => {
+                let name = &after[..end];
+                match std::env::var(name) {
+                    Ok(var) if !name.is_empty() => result.push_str(&var),
+                    _ => {
+                        result.push('%');
+                        result.push_str(name);
+                        result.push('%');
+                    }
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push('%');
+                rest = after;
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// 读取以 NUL 结尾的 ANSI 字符串（非 UTF-8 字节按有损方式转换）
+fn ansi_until_nul(data: &[u8]) -> String {
+    let end = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..end]).to_string()
+}
+
+/// 读取以 NUL 结尾的 UTF-16LE 字符串
+fn utf16_until_nul(data: &[u8]) -> String {
+    let units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .take_while(|u| *u != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// 顺序读取器
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn u16_at(&self, offset: usize) -> Result<u16, String> {
+        read_u16(self.data, offset).ok_or_else(|| "快捷方式数据不完整".to_string())
+    }
+
+    fn u32_at(&self, offset: usize) -> Result<u32, String> {
+        read_u32(self.data, offset).ok_or_else(|| "快捷方式数据不完整".to_string())
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), String> {
+        if self.pos + len > self.data.len() {
+            return Err("快捷方式数据不完整".to_string());
+        }
+        self.pos += len;
+        Ok(())
+    }
+
+    /// StringData：字符数 (u16) + 字符（Unicode 为 UTF-16LE，否则为 ANSI，均不含结尾 NUL）
+    fn counted_string(&mut self, unicode: bool) -> Result<String, String> {
+        let count = self.u16_at(self.pos)? as usize;
+        let len = if unicode { count * 2 } else { count };
+        let start = self.pos + 2;
+        let bytes = self
+            .data
+            .get(start..start + len)
+            .ok_or_else(|| "快捷方式字符串越界".to_string())?;
+        self.pos = start + len;
+
+        Ok(if unicode {
+            utf16_until_nul(bytes)
+        } else {
+            ansi_until_nul(bytes)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(|u| u.to_le_bytes()).collect()
+    }
+
+    fn header(flags: u32) -> Vec<u8> {
+        let mut bytes = vec![0u8; HEADER_SIZE];
+        bytes[0..4].copy_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+        bytes[4..20].copy_from_slice(&LINK_CLSID);
+        bytes[20..24].copy_from_slice(&flags.to_le_bytes());
+        bytes
+    }
+
+    /// LinkInfo：头部 0x1C 字节（无 Unicode 偏移），VolumeID 最小结构，ANSI 本地路径和空后缀
+    fn ansi_link_info(base_path: &str) -> Vec<u8> {
+        let volume_id = [0x10u8, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 0x10, 0, 0, 0];
+        let volume_offset = 0x1Cu32;
+        let base_offset = volume_offset + volume_id.len() as u32;
+        let suffix_offset = base_offset + base_path.len() as u32 + 1;
+        let size = suffix_offset + 1;
+
+        let mut info = Vec::new();
+        for value in [size, 0x1C, VOLUME_ID_AND_LOCAL_BASE_PATH, volume_offset, base_offset, 0, suffix_offset] {
+            info.extend_from_slice(&value.to_le_bytes());
+        }
+        info.extend_from_slice(&volume_id);
+        info.extend_from_slice(base_path.as_bytes());
+        info.push(0);
+        info.push(0);
+        info
+    }
+
+    #[test]
+    fn test_parse_local_target_with_id_list_and_strings() {
+        let mut bytes = header(HAS_LINK_TARGET_ID_LIST | HAS_LINK_INFO | HAS_RELATIVE_PATH | HAS_WORKING_DIR | IS_UNICODE);
+        // IDList：一个 4 字节的项 + 结束符
+        bytes.extend_from_slice(&[6, 0, 4, 0, 0xAA, 0xBB, 0, 0]);
+        bytes.extend_from_slice(&ansi_link_info("C:\\Program Files\\BitBrowser\\BitBrowser.exe"));
+        for s in ["..\\..\\BitBrowser\\BitBrowser.exe", "C:\\Program Files\\BitBrowser"] {
+            bytes.extend_from_slice(&(s.encode_utf16().count() as u16).to_le_bytes());
+            bytes.extend_from_slice(&utf16(s));
+        }
+        bytes.extend_from_slice(&[0, 0, 0, 0]);
+
+        let link = ShellLink::parse(&bytes).unwrap();
+        assert_eq!(
+            link.link_info_target.as_deref(),
+            Some("C:\\Program Files\\BitBrowser\\BitBrowser.exe")
+        );
+        assert_eq!(link.working_dir.as_deref(), Some("C:\\Program Files\\BitBrowser"));
+        assert_eq!(
+            link.target(None).as_deref(),
+            Some("C:\\Program Files\\BitBrowser\\BitBrowser.exe")
+        );
+    }
+
+    #[test]
+    fn test_parse_unicode_link_info() {
+        // 头部 0x24 字节，带 Unicode 本地路径和后缀偏移
+        let base = utf16("D:\\比特浏览器\\");
+        let suffix = utf16("比特浏览器.exe");
+        let base_offset = 0x24u32;
+        let suffix_offset = base_offset + base.len() as u32 + 2;
+        let size = suffix_offset + suffix.len() as u32 + 2;
+
+        let mut info = Vec::new();
+        for value in [size, 0x24, VOLUME_ID_AND_LOCAL_BASE_PATH, 0, 0, 0, 0, base_offset, suffix_offset] {
+            info.extend_from_slice(&value.to_le_bytes());
+        }
+        info.extend_from_slice(&base);
+        info.extend_from_slice(&[0, 0]);
+        info.extend_from_slice(&suffix);
+        info.extend_from_slice(&[0, 0]);
+
+        let mut bytes = header(HAS_LINK_INFO | IS_UNICODE);
+        bytes.extend_from_slice(&info);
+
+        let link = ShellLink::parse(&bytes).unwrap();
+        assert_eq!(link.link_info_target.as_deref(), Some("D:\\比特浏览器\\比特浏览器.exe"));
+    }
+
+    #[test]
+    fn test_environment_block_and_relative_fallback() {
+        let mut bytes = header(HAS_RELATIVE_PATH);
+        let relative = ".\\BitBrowser.exe";
+        bytes.extend_from_slice(&(relative.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(relative.as_bytes());
+
+        let mut block = vec![0u8; 0x314];
+        block[0..4].copy_from_slice(&0x314u32.to_le_bytes());
+        block[4..8].copy_from_slice(&ENVIRONMENT_BLOCK_SIGNATURE.to_le_bytes());
+        let target = utf16("%SHELL_LINK_TEST_DIR%\\BitBrowser\\BitBrowser.exe");
+        block[268..268 + target.len()].copy_from_slice(&target);
+        bytes.extend_from_slice(&block);
+        bytes.extend_from_slice(&[0, 0, 0, 0]);
+
+        let link = ShellLink::parse(&bytes).unwrap();
+        assert_eq!(link.relative_path.as_deref(), Some(relative));
+
+        std::env::set_var("SHELL_LINK_TEST_DIR", "C:\\Users\\me\\AppData\\Local");
+        assert_eq!(
+            link.target(None).as_deref(),
+            Some("C:\\Users\\me\\AppData\\Local\\BitBrowser\\BitBrowser.exe")
+        );
+
+        let without_env = ShellLink {
+            environment_target: None,
+            ..link
+        };
+        let target = without_env.target(Some(Path::new("links"))).unwrap();
+        assert!(target.starts_with("links") && target.ends_with("BitBrowser.exe"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_data() {
+        assert!(ShellLink::parse(b"not a shortcut").is_err());
+
+        // LinkInfo 长度越界
+        let mut bytes = header(HAS_LINK_INFO);
+        bytes.extend_from_slice(&[0xFF, 0, 0, 0]);
+        assert!(ShellLink::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_expand_environment_variables() {
+        std::env::set_var("SHELL_LINK_EXPAND", "X");
+        assert_eq!(expand_environment_variables("%SHELL_LINK_EXPAND%\\a"), "X\\a");
+        assert_eq!(expand_environment_variables("%UNDEFINED_SHELL_LINK%\\a"), "%UNDEFINED_SHELL_LINK%\\a");
+        assert_eq!(expand_environment_variables("100%"), "100%");
+    }
+}
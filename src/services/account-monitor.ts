@@ -119,8 +119,6 @@ export class AccountMonitorService {
       if (filterMyAccounts && currentUserName) {
         // 获取比特浏览器列表（应用用户筛选）
         const params: any = {
-          page: 0,
-          pageSize: 1000,
           createdName: currentUserName
         };
 
@@ -367,8 +365,6 @@ export class AccountMonitorService {
       if (filterMyAccounts && currentUserName) {
         // 获取比特浏览器列表（应用用户筛选）
         const params: any = {
-          page: 0,
-          pageSize: 1000,
           createdName: currentUserName
         };
 
@@ -419,10 +419,8 @@ export class AccountSyncService {
       const registeredBrowserIds = Object.keys(registeredAccounts);
 
       // 3. 获取所有比特浏览器列表（应用用户筛选）
-      const params: any = {
-        page: 0,
-        pageSize: 1000
-      };
+      // 不传 page 时后端遍历所有分页
+      const params: any = {};
 
       if (filterUserName) {
         params.createdName = filterUserName;
@@ -568,10 +566,7 @@ export class AccountSyncService {
       }
 
       // 2. 获取本地浏览器列表
-      const localBrowsersResponse = await invoke<any>('get_browser_list', {
-        page: 0,
-        pageSize: 1000
-      });
+      const localBrowsersResponse = await invoke<any>('get_browser_list');
 
       if (!localBrowsersResponse.success || !localBrowsersResponse.data?.list) {
         console.error('[批量同步名称] 获取本地浏览器列表失败');
@@ -623,10 +618,7 @@ export class AccountSyncService {
       if (result.updated > 0) {
         try {
           // 重新获取最新的浏览器列表
-          const updatedResponse = await invoke<any>('get_browser_list', {
-            page: 0,
-            pageSize: 1000
-          });
+          const updatedResponse = await invoke<any>('get_browser_list');
 
           if (updatedResponse.success && updatedResponse.data?.list) {
             // 更新 browserStore